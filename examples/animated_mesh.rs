@@ -8,7 +8,7 @@ use bevy::{
     core_pipeline::prepass::DepthPrepass, light::CascadeShadowConfigBuilder, prelude::*,
     world_serialization::WorldInstanceReady,
 };
-use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera};
+use bevy_mesh_outline::{InheritOutline, MeshOutline, MeshOutlinePlugin, OutlineCamera};
 
 const GLTF_PATH: &str = "Fox.glb";
 
@@ -47,28 +47,17 @@ fn setup_fox(
         WorldAssetRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(GLTF_PATH)));
 
     commands
-        .spawn((animation_to_play, mesh_scene))
+        .spawn((
+            animation_to_play,
+            mesh_scene,
+            // Outline every mesh of the scene once it has loaded
+            MeshOutline::new(10.0),
+            InheritOutline,
+        ))
         // Triggered after scene finishes loading
-        .observe(initialize_outlines)
         .observe(initialize_animations);
 }
 
-// Called when the scene is ready and meshes exist
-fn initialize_outlines(
-    trigger: On<WorldInstanceReady>,
-    mut commands: Commands,
-    children: Query<&Children>,
-    mesh_entities: Query<Entity, With<Mesh3d>>,
-) {
-    for child in children.iter_descendants(trigger.event().entity) {
-        // If the current entity in scene is a mesh - add outline
-        if let Ok(mesh_entity) = mesh_entities.get(child) {
-            commands.entity(mesh_entity).insert(MeshOutline::new(10.0));
-        }
-    }
-    commands.entity(trigger.observer()).despawn();
-}
-
 fn initialize_animations(
     trigger: On<WorldInstanceReady>,
     mut commands: Commands,
@@ -5,7 +5,7 @@ use bevy::{
     post_process::bloom::Bloom,
     prelude::*,
 };
-use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera};
+use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera, OutlineFalloff};
 
 fn main() {
     App::new()
@@ -63,7 +63,9 @@ fn setup(
         MeshMaterial3d(materials.add(Color::from(BLUE))),
         Transform::from_xyz(0.0, 1.0, 0.0),
         // Add outline
-        MeshOutline::new(10.0).with_color(Color::from(RED)),
+        MeshOutline::new(10.0)
+            .with_color(Color::from(RED))
+            .with_falloff(OutlineFalloff::Smoothstep),
         OutlineGlow {
             intensity: 20.0,
             period: 0.2,
@@ -6,7 +6,7 @@ use bevy::{
     input::keyboard::KeyboardInput,
     prelude::*,
 };
-use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera};
+use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera, OutlineContactLine};
 
 fn main() {
     App::new()
@@ -28,7 +28,7 @@ fn main() {
 }
 
 #[derive(Component)]
-pub struct OutlinePriority(f32);
+pub struct ToggledPriority(i32);
 
 #[derive(Resource, Default)]
 struct PriorityToggle {
@@ -68,14 +68,17 @@ fn setup(
         MeshMaterial3d(materials.add(Color::from(SILVER))),
     ));
 
-    // Yellow cube with red outline, low priority
+    // Yellow cube with red outline, low priority, and a line where it cuts
+    // through the sphere
     commands.spawn((
         Mesh3d(meshes.add(Cuboid::default())),
         MeshMaterial3d(materials.add(Color::from(YELLOW))),
         Transform::from_xyz(0.0, 1.0, 0.0)
             .with_rotation(Quat::from_rotation_x(PI / 5.0) * Quat::from_rotation_y(PI / 3.0)),
-        MeshOutline::new(10.0).with_color(Color::from(RED)),
-        OutlinePriority(1.0),
+        MeshOutline::new(10.0)
+            .with_color(Color::from(RED))
+            .with_contact_line(OutlineContactLine::new(Color::from(RED)).with_width(4.0)),
+        ToggledPriority(1),
     ));
 
     // Blue sphere with green outline, high priority
@@ -86,7 +89,7 @@ fn setup(
         MeshOutline::new(10.0)
             .with_color(Color::from(GREEN))
             .with_intensity(10.0),
-        OutlinePriority(10.0),
+        ToggledPriority(10),
     ));
 }
 
@@ -116,13 +119,13 @@ fn toggle_priority(input: Res<ButtonInput<KeyCode>>, mut priority_toggle: ResMut
 
 fn update_outline_priorities(
     priority_toggle: Res<PriorityToggle>,
-    mut outline_query: Query<(&mut MeshOutline, &OutlinePriority)>,
+    mut outline_query: Query<(&mut MeshOutline, &ToggledPriority)>,
 ) {
     for (mut outline, priority) in &mut outline_query {
         if priority_toggle.enabled {
             outline.priority = priority.0;
         } else {
-            outline.priority = 0.0;
+            outline.priority = 0;
         }
     }
 }
@@ -4,7 +4,7 @@ use bevy::{
     input::keyboard::KeyboardInput,
     prelude::*,
 };
-use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera};
+use bevy_mesh_outline::{MeshOutline, MeshOutlinePlugin, OutlineCamera, OutlineWidth};
 
 fn main() {
     App::new()
@@ -82,7 +82,9 @@ fn update_size(input: Res<ButtonInput<KeyCode>>, mut outline: Single<&mut MeshOu
         delta += change_speed;
     }
 
-    outline.width += delta;
+    if let OutlineWidth::Pixels(width) = &mut outline.width {
+        *width += delta;
+    }
 }
 
 #[derive(Component)]
@@ -110,7 +112,7 @@ fn update_width_display(
     outline_query: Single<&MeshOutline>,
     mut text_query: Single<&mut Text, With<WidthText>>,
 ) {
-    let width = outline_query.width;
+    let width = outline_query.width.value();
     text_query.0 = format!(
         "Decrease width (Q)\nIncrease width (W)\nCurrent width: {:.1}",
         width
@@ -5,7 +5,7 @@ use bevy::{
         render_resource::{
             BindGroupLayoutDescriptor, BindGroupLayoutEntries, CachedRenderPipelineId,
             FragmentState, PipelineCache, RenderPipelineDescriptor,
-            binding_types::{sampler, texture_2d},
+            binding_types::{sampler, storage_buffer_read_only, texture_2d, uniform_buffer},
         },
         renderer::RenderDevice,
     },
@@ -18,7 +18,10 @@ use wgpu_types::{
     ShaderStages, TextureFormat, TextureSampleType,
 };
 
-use crate::shaders::COMPOSE_SHADER_HANDLE;
+use crate::{
+    shaders::COMPOSE_SHADER_HANDLE,
+    uniforms::{OutlineStyleUniform, OutlineViewUniform},
+};
 
 #[derive(Clone, Resource)]
 pub struct ComposeOutputPipeline {
@@ -38,6 +41,10 @@ pub struct ComposeOutputPipeline {
 /// depth texture (binding 4) is declared as a multisampled depth texture, which
 /// is how Bevy's prepass exposes depth when MSAA is enabled. The outline depth
 /// texture (binding 5) is owned by this plugin and is always single-sampled.
+/// Binding 6 holds the per-appearance [`OutlineStyleUniform`]s, binding 7
+/// the view's [`OutlineViewUniform`], binding 8 the runner-up flood seeds,
+/// binding 9 the normals and depths of the mask and binding 10 the coverage of
+/// the scene edges.
 fn compose_layout(multisampled: bool) -> BindGroupLayoutDescriptor {
     let global_depth = if multisampled {
         texture_depth_2d_multisampled()
@@ -56,6 +63,11 @@ fn compose_layout(multisampled: bool) -> BindGroupLayoutDescriptor {
                 texture_2d(TextureSampleType::Float { filterable: true }),
                 global_depth,
                 texture_depth_2d(),
+                storage_buffer_read_only::<OutlineStyleUniform>(false),
+                uniform_buffer::<OutlineViewUniform>(true),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                texture_2d(TextureSampleType::Float { filterable: true }),
             ),
         ),
     )
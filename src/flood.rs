@@ -9,21 +9,30 @@ use bevy::{
             CachedRenderPipelineId, DynamicUniformBuffer, FragmentState, Operations, PipelineCache,
             RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
             RenderPipelineDescriptor, Sampler, SamplerDescriptor, ShaderType,
-            binding_types::{sampler, texture_2d, uniform_buffer},
+            binding_types::{sampler, storage_buffer_read_only, texture_2d, uniform_buffer},
         },
         renderer::{RenderContext, RenderDevice, RenderQueue},
         texture::CachedTexture,
     },
 };
-use bevy_render::render_resource::{TextureView, binding_types::texture_depth_2d};
+use bevy_render::{
+    camera::ExtractedCamera,
+    render_resource::{BindingResource, TextureView, binding_types::texture_depth_2d},
+    view::ExtractedView,
+};
 use wgpu_types::{
     ColorTargetState, ColorWrites, FilterMode, MultisampleState, PrimitiveState,
     SamplerBindingType, ShaderStages, TextureFormat, TextureSampleType,
 };
 
-use crate::shaders::FLOOD_SHADER_HANDLE;
+use crate::{
+    shaders::FLOOD_SHADER_HANDLE,
+    texture::FloodTextures,
+    uniforms::{OutlineStyleUniform, OutlineViewUniform},
+    view::ExtractedOutlineView,
+};
 
-use super::{ExtractedOutline, OutlineCamera};
+use super::{ExtractedOutline, OutlineCamera, OutlineWidth};
 
 #[derive(ShaderType)]
 pub struct JumpFloodUniform {
@@ -32,30 +41,134 @@ pub struct JumpFloodUniform {
 
 #[derive(Component, Default, Clone)]
 pub struct FloodSettings {
+    /// Furthest any seed reaches, in pixels of the flood textures: the widest
+    /// band plus its noise, offset and smoothed edge.
     pub width: f32,
 }
 
+/// Upper bound, in physical pixels, of the width `outline` resolves to in a
+/// view. Mirrors the width resolution of the mask and flood shaders, except
+/// that centered outlines aren't halved and pulses are at their widest.
+/// World widths of meshes closer than the near plane, `near_w` in clip w,
+/// resolve as if on it.
+pub(crate) fn resolve_width(
+    outline: &ExtractedOutline,
+    clip_from_world: &Mat4,
+    near_w: f32,
+    pixels_per_unit: f32,
+    viewport_height: f32,
+    scale_factor: f32,
+) -> f32 {
+    let value = if outline.pulse_period > 0.0 {
+        outline.pulse_width.max_element()
+    } else {
+        outline.width.value()
+    };
+    let width = match outline.width {
+        OutlineWidth::Pixels(_) => value,
+        OutlineWidth::LogicalPixels(_) => value * scale_factor,
+        OutlineWidth::World(_) => {
+            let [x, y, z] = outline.world_from_local;
+            let origin = Vec3::new(x.w, y.w, z.w);
+            let clip = *clip_from_world * origin.extend(1.0);
+            value * pixels_per_unit / clip.w.max(near_w)
+        }
+        OutlineWidth::ScreenFraction(_) => value * viewport_height,
+    };
+    width.clamp(outline.min_width, outline.max_width.max(outline.min_width))
+}
+
+/// Clip w on the near plane of `clip_from_view`: its distance from the camera
+/// under a perspective projection, and one under an orthographic one. Must
+/// match `world_scale` in `mask.wgsl`.
+fn near_clip_w(clip_from_view: &Mat4) -> f32 {
+    if clip_from_view.w_axis.w == 1.0 {
+        1.0
+    } else {
+        clip_from_view.w_axis.z
+    }
+}
+
 pub fn prepare_flood_settings(
     mut commands: Commands,
     outlines: Query<&ExtractedOutline>,
-    cameras: Query<Entity, With<OutlineCamera>>,
+    cameras: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &ExtractedCamera,
+            &ExtractedOutlineView,
+        ),
+        With<OutlineCamera>,
+    >,
 ) {
-    let mut max_size: f32 = 0.0;
-    for outline in outlines.iter() {
-        max_size = max_size.max(outline.width);
-    }
-    let settings = FloodSettings { width: max_size };
+    for (entity, view, camera, outline_view) in cameras.iter() {
+        let clip_from_world = view
+            .clip_from_world
+            .unwrap_or_else(|| view.clip_from_view * view.world_from_view.to_matrix().inverse());
+        let near_w = near_clip_w(&view.clip_from_view);
+        let viewport_height = view.viewport.w as f32;
+        let pixels_per_unit = view.clip_from_view.y_axis.y * 0.5 * viewport_height;
+
+        let mut max_size: f32 = 0.0;
+        let mut max_spread: f32 = 0.0;
+        for outline in outlines.iter() {
+            if !outline_view.shows(outline) {
+                continue;
+            }
+            for layer in outline.iter_layers() {
+                let width = resolve_width(
+                    layer,
+                    &clip_from_world,
+                    near_w,
+                    pixels_per_unit,
+                    viewport_height,
+                    outline_view.scale_factor,
+                );
+                max_size = max_size.max(width);
+                max_spread = max_spread.max(band_spread(layer));
+            }
+        }
 
-    for entity in cameras.iter() {
-        commands.entity(entity).insert(settings.clone());
+        let texture_size =
+            outline_view.texture_size(camera.physical_target_size.unwrap_or_default());
+        commands.entity(entity).insert(FloodSettings {
+            width: flood_spread(outline_view, texture_size, max_size, max_spread),
+        });
     }
 }
 
+/// How many physical pixels past its width the band of `layer` can reach.
+/// Must match `seed` in `flood.wgsl`.
+fn band_spread(layer: &ExtractedOutline) -> f32 {
+    // Noise can widen the band by its amplitude, and an offset moves it away
+    // from the silhouette, blurred edge included.
+    layer.noise.x + layer.offset.length() + layer.offset_blur * 0.5
+}
+
+/// How far, in pixels of the flood textures, the flood must carry seeds in
+/// `view` for bands up to `width` physical pixels wide that noise or offsets
+/// push up to `spread` pixels further. Applies the camera settings like the
+/// shaders, and never exceeds the largest dimension of the `texture_size`
+/// flood textures, which no seed has to cross more than once.
+fn flood_spread(view: &ExtractedOutlineView, texture_size: UVec2, width: f32, spread: f32) -> f32 {
+    // The smoothed edge reaches half its softness past the band.
+    let spread = ((width * view.width_scale).min(view.max_width) + spread) * view.resolution_scale
+        + view.edge_softness() * 0.5;
+    spread.min(texture_size.max_element() as f32)
+}
+
 #[derive(Resource)]
 pub struct JumpFloodPipeline {
     pub layout: BindGroupLayoutDescriptor,
     pub sampler: Sampler,
     pub pipeline_id: CachedRenderPipelineId,
+    /// Turns the mask into the seeds of the first flood pass.
+    pub seed_pipeline_id: CachedRenderPipelineId,
+    /// Variants that also keep the runner-up seed of each pixel, for views
+    /// with two [`crate::OutlineCameraSettings::overlap_candidates`].
+    pub layered_pipeline_id: CachedRenderPipelineId,
+    pub layered_seed_pipeline_id: CachedRenderPipelineId,
     pub lookup_buffer: DynamicUniformBuffer<JumpFloodUniform>,
     pub lookup_offsets: Vec<u32>,
 }
@@ -75,6 +188,10 @@ impl FromWorld for JumpFloodPipeline {
                     texture_depth_2d(),                                        // depth_texture
                     texture_2d(TextureSampleType::Float { filterable: true }), // color_texture
                     texture_2d(TextureSampleType::Float { filterable: true }), // appearance_texture
+                    storage_buffer_read_only::<OutlineStyleUniform>(false),    // styles
+                    uniform_buffer::<OutlineViewUniform>(true),                // view
+                    texture_2d(TextureSampleType::Float { filterable: true }), // secondary_flood_texture
+                    texture_2d(TextureSampleType::Float { filterable: true }), // edge_texture
                 ),
             ),
         );
@@ -86,29 +203,57 @@ impl FromWorld for JumpFloodPipeline {
 
         let fullscreen_shader = world.resource::<FullscreenShader>().clone();
 
-        let pipeline_id =
-            world
-                .resource_mut::<PipelineCache>()
-                .queue_render_pipeline(RenderPipelineDescriptor {
-                    label: Some("outline_jump_flood_pipeline".into()),
-                    layout: vec![layout.clone()],
-                    vertex: fullscreen_shader.to_vertex_state(),
-                    fragment: Some(FragmentState {
-                        shader: FLOOD_SHADER_HANDLE,
-                        shader_defs: vec![],
-                        entry_point: Some("fragment".into()),
-                        targets: vec![Some(ColorTargetState {
-                            format: TextureFormat::Rgba32Float,
-                            blend: None,
-                            write_mask: ColorWrites::ALL,
-                        })],
-                    }),
-                    primitive: PrimitiveState::default(),
-                    depth_stencil: None,
-                    multisample: MultisampleState::default(),
-                    immediate_size: 0,
-                    zero_initialize_workgroup_memory: false,
-                });
+        let make_descriptor = |label: &'static str, entry_point: &'static str, layered: bool| {
+            let target = Some(ColorTargetState {
+                format: TextureFormat::Rgba32Float,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            });
+            let (shader_defs, targets) = if layered {
+                (vec!["LAYERED".into()], vec![target.clone(), target])
+            } else {
+                (vec![], vec![target])
+            };
+
+            RenderPipelineDescriptor {
+                label: Some(label.into()),
+                layout: vec![layout.clone()],
+                vertex: fullscreen_shader.to_vertex_state(),
+                fragment: Some(FragmentState {
+                    shader: FLOOD_SHADER_HANDLE,
+                    shader_defs,
+                    entry_point: Some(entry_point.into()),
+                    targets,
+                }),
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                immediate_size: 0,
+                zero_initialize_workgroup_memory: false,
+            }
+        };
+
+        let cache = world.resource_mut::<PipelineCache>();
+        let pipeline_id = cache.queue_render_pipeline(make_descriptor(
+            "outline_jump_flood_pipeline",
+            "fragment",
+            false,
+        ));
+        let seed_pipeline_id = cache.queue_render_pipeline(make_descriptor(
+            "outline_flood_seed_pipeline",
+            "seed",
+            false,
+        ));
+        let layered_pipeline_id = cache.queue_render_pipeline(make_descriptor(
+            "outline_jump_flood_layered_pipeline",
+            "fragment",
+            true,
+        ));
+        let layered_seed_pipeline_id = cache.queue_render_pipeline(make_descriptor(
+            "outline_flood_seed_layered_pipeline",
+            "seed",
+            true,
+        ));
 
         let render_queue = world.resource::<RenderQueue>();
         let mut uniform_buffer = DynamicUniformBuffer::new_with_alignment(
@@ -126,6 +271,9 @@ impl FromWorld for JumpFloodPipeline {
             layout,
             sampler,
             pipeline_id,
+            seed_pipeline_id,
+            layered_pipeline_id,
+            layered_seed_pipeline_id,
             lookup_buffer: uniform_buffer,
             lookup_offsets: offsets,
         }
@@ -135,6 +283,9 @@ impl FromWorld for JumpFloodPipeline {
 pub struct JumpFloodPass<'w> {
     pub pipeline: &'w JumpFloodPipeline,
     render_pipeline: &'w RenderPipeline,
+    seed_pipeline: &'w RenderPipeline,
+    layered_render_pipeline: &'w RenderPipeline,
+    layered_seed_pipeline: &'w RenderPipeline,
     pipeline_cache: &'w PipelineCache,
 }
 
@@ -143,25 +294,82 @@ impl<'w> JumpFloodPass<'w> {
         let pipeline = world.resource::<JumpFloodPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
         let render_pipeline = pipeline_cache.get_render_pipeline(pipeline.pipeline_id)?;
+        let seed_pipeline = pipeline_cache.get_render_pipeline(pipeline.seed_pipeline_id)?;
+        let layered_render_pipeline =
+            pipeline_cache.get_render_pipeline(pipeline.layered_pipeline_id)?;
+        let layered_seed_pipeline =
+            pipeline_cache.get_render_pipeline(pipeline.layered_seed_pipeline_id)?;
 
         Some(Self {
             pipeline,
             render_pipeline,
+            seed_pipeline,
+            layered_render_pipeline,
+            layered_seed_pipeline,
             pipeline_cache,
         })
     }
 
-    #[allow(clippy::too_many_arguments)]
+    /// Runs one jump flood pass with a step length of `2^size` pixels, from
+    /// the input to the output textures of `textures`.
     pub fn execute(
         &mut self,
         render_context: &mut RenderContext<'_, '_>,
-        input: &CachedTexture,
-        output: &CachedTexture,
-        depth_texture: &TextureView,
-        color_texture: &TextureView,
-        appearance_texture: &TextureView,
+        textures: &FloodTextures,
+        inputs: &FloodInputs,
+        size: u32,
+    ) {
+        let render_pipeline = if textures.is_layered() {
+            self.layered_render_pipeline
+        } else {
+            self.render_pipeline
+        };
+        self.draw(
+            render_context,
+            render_pipeline,
+            "outline_jump_flood_pass",
+            textures,
+            inputs,
+            size,
+        );
+    }
+
+    /// Converts the mask in the input texture into the seeds the flood passes
+    /// start from.
+    pub fn execute_seed(
+        &mut self,
+        render_context: &mut RenderContext<'_, '_>,
+        textures: &FloodTextures,
+        inputs: &FloodInputs,
+    ) {
+        let seed_pipeline = if textures.is_layered() {
+            self.layered_seed_pipeline
+        } else {
+            self.seed_pipeline
+        };
+        self.draw(
+            render_context,
+            seed_pipeline,
+            "outline_flood_seed_pass",
+            textures,
+            inputs,
+            0,
+        );
+    }
+
+    fn draw(
+        &self,
+        render_context: &mut RenderContext<'_, '_>,
+        render_pipeline: &RenderPipeline,
+        label: &'static str,
+        textures: &FloodTextures,
+        inputs: &FloodInputs,
         size: u32,
     ) {
+        let input = textures.input();
+        // Without runner-up textures the binding is never read, so any flood
+        // texture will do.
+        let secondary_input = textures.secondary_input().unwrap_or(input);
         let bind_group = render_context.render_device().create_bind_group(
             "outline_jump_flood_bind_group",
             &self
@@ -171,32 +379,175 @@ impl<'w> JumpFloodPass<'w> {
                 &input.default_view,
                 &self.pipeline.sampler,
                 self.pipeline.lookup_buffer.binding().unwrap(),
-                depth_texture,
-                color_texture,
-                appearance_texture,
+                inputs.depth_texture,
+                inputs.color_texture,
+                inputs.appearance_texture,
+                inputs.styles.clone(),
+                inputs.view_uniforms.clone(),
+                &secondary_input.default_view,
+                inputs.edge_texture,
             )),
         );
 
+        let mut color_attachments = vec![color_attachment(textures.output())];
+        if let Some(secondary_output) = textures.secondary_output() {
+            color_attachments.push(color_attachment(secondary_output));
+        }
+
         let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("outline_jump_flood_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: &output.default_view,
-                resolve_target: None,
-                ops: Operations::default(),
-                depth_slice: None,
-            })],
+            label: Some(label),
+            color_attachments: &color_attachments,
             depth_stencil_attachment: None,
             timestamp_writes: None,
             occlusion_query_set: None,
             multiview_mask: None,
         });
 
-        render_pass.set_render_pipeline(self.render_pipeline);
+        render_pass.set_render_pipeline(render_pipeline);
         render_pass.set_bind_group(
             0,
             &bind_group,
-            &[self.pipeline.lookup_offsets[size as usize]],
+            &[
+                self.pipeline.lookup_offsets[size as usize],
+                inputs.view_offset,
+            ],
         );
         render_pass.draw(0..3, 0..1);
     }
 }
+
+fn color_attachment(texture: &CachedTexture) -> Option<RenderPassColorAttachment<'_>> {
+    Some(RenderPassColorAttachment {
+        view: &texture.default_view,
+        resolve_target: None,
+        ops: Operations::default(),
+        depth_slice: None,
+    })
+}
+
+/// Textures and buffers every flood pass of a view reads, besides the flood
+/// texture of the previous pass.
+pub struct FloodInputs<'a> {
+    pub depth_texture: &'a TextureView,
+    pub color_texture: &'a TextureView,
+    pub appearance_texture: &'a TextureView,
+    pub edge_texture: &'a TextureView,
+    pub styles: BindingResource<'a>,
+    pub view_uniforms: BindingResource<'a>,
+    pub view_offset: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::camera::CameraProjection;
+
+    use super::*;
+
+    fn resolve(outline: &ExtractedOutline) -> f32 {
+        // A projection whose clip w is the world z, with 100 pixels per world
+        // unit at unit depth, in a 1000 pixel high viewport at a scale factor
+        // of 2.
+        // The near plane is at a depth of 0.5.
+        let clip_from_world = Mat4::from_cols(Vec4::X, Vec4::Y, Vec4::Z + Vec4::W, Vec4::ZERO);
+        resolve_width(outline, &clip_from_world, 0.5, 100.0, 1000.0, 2.0)
+    }
+
+    fn at(width: OutlineWidth, depth: f32) -> ExtractedOutline {
+        ExtractedOutline {
+            width,
+            world_from_local: [
+                Vec4::new(1.0, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, 1.0, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, 1.0, depth),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn widths_resolve_to_physical_pixels() {
+        assert_eq!(resolve(&at(OutlineWidth::Pixels(3.0), 1.0)), 3.0);
+        assert_eq!(resolve(&at(OutlineWidth::LogicalPixels(3.0), 1.0)), 6.0);
+        assert_eq!(resolve(&at(OutlineWidth::ScreenFraction(0.01), 1.0)), 10.0);
+        assert_eq!(resolve(&at(OutlineWidth::World(0.1), 1.0)), 10.0);
+        assert_eq!(resolve(&at(OutlineWidth::World(0.1), 4.0)), 2.5);
+    }
+
+    #[test]
+    fn world_widths_closer_than_the_near_plane_resolve_on_it() {
+        let on_near_plane = resolve(&at(OutlineWidth::World(0.1), 0.5));
+        assert_eq!(on_near_plane, 20.0);
+        assert_eq!(resolve(&at(OutlineWidth::World(0.1), 0.01)), on_near_plane);
+        // An origin behind the camera, e.g. of a large mesh around it.
+        assert_eq!(resolve(&at(OutlineWidth::World(0.1), -3.0)), on_near_plane);
+    }
+
+    #[test]
+    fn near_planes_come_from_the_projection() {
+        let perspective = PerspectiveProjection {
+            near: 0.25,
+            ..Default::default()
+        };
+        assert_eq!(near_clip_w(&perspective.get_clip_from_view()), 0.25);
+        let orthographic = OrthographicProjection {
+            near: 2.0,
+            ..OrthographicProjection::default_3d()
+        };
+        assert_eq!(near_clip_w(&orthographic.get_clip_from_view()), 1.0);
+    }
+
+    #[test]
+    fn resolved_widths_are_clamped_and_pulses_at_their_widest() {
+        let clamped = ExtractedOutline {
+            min_width: 4.0,
+            max_width: 8.0,
+            ..at(OutlineWidth::Pixels(20.0), 1.0)
+        };
+        assert_eq!(resolve(&clamped), 8.0);
+        assert_eq!(
+            resolve(&ExtractedOutline {
+                width: OutlineWidth::Pixels(1.0),
+                ..clamped
+            }),
+            4.0
+        );
+
+        let pulse = ExtractedOutline {
+            pulse_width: Vec2::new(2.0, 6.0),
+            pulse_period: 1.0,
+            ..at(OutlineWidth::LogicalPixels(1.0), 1.0)
+        };
+        assert_eq!(resolve(&pulse), 12.0);
+    }
+
+    #[test]
+    fn the_flood_reaches_past_noisy_and_smoothed_band_edges() {
+        let view = ExtractedOutlineView::default();
+        let texture_size = UVec2::new(64, 32);
+        assert_eq!(flood_spread(&view, texture_size, 6.0, 0.0), 6.5);
+        assert_eq!(flood_spread(&view, texture_size, 6.0, 2.0), 8.5);
+        // Nothing is further away than across the whole texture.
+        assert_eq!(flood_spread(&view, texture_size, 1e9, 0.0), 64.0);
+
+        // Noise isn't capped by the camera's width settings.
+        let scaled = ExtractedOutlineView {
+            width_scale: 2.0,
+            max_width: 10.0,
+            resolution_scale: 0.5,
+            ..view
+        };
+        assert_eq!(flood_spread(&scaled, texture_size, 6.0, 2.0), 6.5);
+    }
+
+    #[test]
+    fn offset_bands_spread_by_their_offset_and_half_their_blur() {
+        assert_eq!(band_spread(&ExtractedOutline::default()), 0.0);
+        let shadow = ExtractedOutline {
+            noise: Vec3::new(2.0, 0.0, 0.0),
+            offset: Vec2::new(3.0, -4.0),
+            offset_blur: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(band_spread(&shadow), 8.0);
+    }
+}
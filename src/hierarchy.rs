@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use super::{InheritOutline, InheritedOutline, MeshOutline};
+
+/// The closest ancestor of `entity` whose outline is inherited, if any.
+fn nearest_root(
+    entity: Entity,
+    parents: &Query<&ChildOf>,
+    roots: &Query<(Entity, Ref<MeshOutline>, Ref<InheritOutline>)>,
+) -> Option<Entity> {
+    parents
+        .iter_ancestors(entity)
+        .find(|&ancestor| roots.contains(ancestor))
+}
+
+/// Copies the outline of every [`InheritOutline`] entity onto its descendant
+/// meshes, and removes copies whose source no longer applies.
+///
+/// Meshes with an outline of their own keep it, and a mesh below several
+/// inheriting entities takes the outline of the closest one.
+#[allow(clippy::type_complexity)]
+pub(crate) fn propagate_outlines(
+    mut commands: Commands,
+    roots: Query<(Entity, Ref<MeshOutline>, Ref<InheritOutline>)>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    meshes: Query<(Has<MeshOutline>, Has<InheritedOutline>), With<Mesh3d>>,
+    moved_meshes: Query<Entity, (With<Mesh3d>, Or<(Added<Mesh3d>, Changed<ChildOf>)>)>,
+    inherited: Query<(Entity, &InheritedOutline)>,
+) {
+    let inherit = |commands: &mut Commands, entity: Entity, source: Entity| {
+        let Ok((_, outline, _)) = roots.get(source) else {
+            return;
+        };
+        commands
+            .entity(entity)
+            .insert(((*outline).clone(), InheritedOutline { source }));
+    };
+    // Meshes with an outline that wasn't inherited are left alone.
+    let has_own_outline = |entity: Entity| {
+        meshes
+            .get(entity)
+            .is_ok_and(|(has_outline, is_inherited)| has_outline && !is_inherited)
+    };
+
+    // Drop or reassign copies whose source stopped inheriting, lost its
+    // outline or is no longer the closest inheriting ancestor.
+    for (entity, inherited) in inherited.iter() {
+        match nearest_root(entity, &parents, &roots) {
+            Some(source) if source == inherited.source => {}
+            Some(source) => inherit(&mut commands, entity, source),
+            None => {
+                commands
+                    .entity(entity)
+                    .remove::<(MeshOutline, InheritedOutline)>();
+            }
+        }
+    }
+
+    // Push changed outlines down to their meshes.
+    for (root, outline, inherit_outline) in roots.iter() {
+        if !outline.is_changed() && !inherit_outline.is_added() {
+            continue;
+        }
+        for descendant in children.iter_descendants(root) {
+            if !meshes.contains(descendant) || has_own_outline(descendant) {
+                continue;
+            }
+            if nearest_root(descendant, &parents, &roots) == Some(root) {
+                inherit(&mut commands, descendant, root);
+            }
+        }
+    }
+
+    // Meshes spawned or reparented below an inheriting entity, e.g. when a
+    // scene finishes loading.
+    for entity in moved_meshes.iter() {
+        if has_own_outline(entity) {
+            continue;
+        }
+        if let Some(source) = nearest_root(entity, &parents, &roots) {
+            inherit(&mut commands, entity, source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::{InheritOutline, InheritedOutline, MeshOutline};
+
+    use super::propagate_outlines;
+
+    /// Meshes spawned below an inheriting entity after it get its outline, and
+    /// lose it again when the entity stops inheriting.
+    #[test]
+    fn inherited_outlines_follow_the_source() {
+        let mut app = App::new();
+        app.add_systems(Update, propagate_outlines);
+
+        let root = app
+            .world_mut()
+            .spawn((MeshOutline::new(4.0), InheritOutline))
+            .id();
+        let own = app
+            .world_mut()
+            .spawn((Mesh3d::default(), MeshOutline::new(1.0), ChildOf(root)))
+            .id();
+        app.update();
+
+        // Spawned later, like the meshes of a loading scene.
+        let late = app.world_mut().spawn(ChildOf(root)).id();
+        let mesh = app
+            .world_mut()
+            .spawn((Mesh3d::default(), ChildOf(late)))
+            .id();
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<InheritedOutline>(mesh).unwrap().source, root);
+        assert_eq!(world.get::<MeshOutline>(mesh).unwrap().width.value(), 4.0);
+        assert_eq!(world.get::<MeshOutline>(own).unwrap().width.value(), 1.0);
+
+        app.world_mut().entity_mut(root).remove::<InheritOutline>();
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<MeshOutline>(mesh).is_none());
+        assert!(world.get::<InheritedOutline>(mesh).is_none());
+        assert!(world.get::<MeshOutline>(own).is_some());
+    }
+
+    /// Changing the source's outline updates the copies, and removing it
+    /// hands them to the next inheriting ancestor or clears them.
+    #[test]
+    fn inherited_outlines_follow_changes_to_the_source() {
+        let mut app = App::new();
+        app.add_systems(Update, propagate_outlines);
+
+        let outer = app
+            .world_mut()
+            .spawn((MeshOutline::new(2.0), InheritOutline))
+            .id();
+        let inner = app
+            .world_mut()
+            .spawn((MeshOutline::new(4.0), InheritOutline, ChildOf(outer)))
+            .id();
+        let mesh = app
+            .world_mut()
+            .spawn((Mesh3d::default(), ChildOf(inner)))
+            .id();
+        app.update();
+
+        let width = |app: &App| {
+            app.world()
+                .get::<MeshOutline>(mesh)
+                .map(|outline| outline.width.value())
+        };
+        assert_eq!(width(&app), Some(4.0));
+
+        app.world_mut().get_mut::<MeshOutline>(inner).unwrap().width = 6.0.into();
+        app.update();
+        assert_eq!(width(&app), Some(6.0));
+
+        app.world_mut().entity_mut(inner).remove::<MeshOutline>();
+        app.update();
+        assert_eq!(width(&app), Some(2.0));
+        let source = app.world().get::<InheritedOutline>(mesh).unwrap().source;
+        assert_eq!(source, outer);
+
+        app.world_mut().entity_mut(outer).remove::<MeshOutline>();
+        app.update();
+        assert_eq!(width(&app), None);
+        assert!(app.world().get::<InheritedOutline>(mesh).is_none());
+    }
+}
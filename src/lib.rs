@@ -1,18 +1,27 @@
 mod compose;
 mod flood;
+mod hierarchy;
 mod mask;
 mod mask_pipeline;
 mod node;
+mod outline_only;
 mod queue;
 mod render;
+mod scene_edges;
 mod shaders;
 mod texture;
+mod transition;
 mod uniforms;
 mod view;
+mod visibility;
 
 use bevy::{
+    camera::visibility::{RenderLayers, VisibilitySystems, check_visibility_cpu_culling},
     core_pipeline::{Core3d, Core3dSystems, core_3d::main_transparent_pass_3d},
-    math::{Affine3, Affine3Ext},
+    math::{
+        Affine3, Affine3Ext,
+        curve::{Curve, EaseFunction},
+    },
     pbr::{
         DrawMesh, MeshPipelineSystems, SetMeshBindGroup, SetMeshViewBindGroup,
         SetMeshViewBindingArrayBindGroup, extract_skins,
@@ -32,13 +41,22 @@ use bevy_render::{
 };
 use compose::ComposeOutputPipeline;
 use flood::{JumpFloodPipeline, prepare_flood_settings};
+use hierarchy::propagate_outlines;
 use mask::MeshOutline3d;
 use mask_pipeline::{MeshMaskPipeline, init_mesh_mask_pipeline};
 use node::mesh_outline_pass;
+use outline_only::{hide_outline_only_meshes, show_outline_only_mesh};
 use queue::queue_outline;
-use render::{OutlineBindGroups, SetOutlineBindGroup, prepare_outline_bind_groups};
+use render::{OutlineBindGroups, OutlineStyles, SetOutlineBindGroup, prepare_outline_bind_groups};
+use scene_edges::{SceneEdgePipeline, update_normal_prepass};
 use texture::prepare_flood_textures;
-use view::update_views;
+use transition::{
+    advance_outline_transitions, start_fade_in, start_fade_out, start_stack_fade_out,
+};
+use view::{
+    OutlineViewUniforms, extract_outline_views, prepare_outline_view_uniforms, update_views,
+};
+use visibility::check_outline_layer_visibility;
 
 use crate::shaders::load_shaders;
 
@@ -59,9 +77,59 @@ impl Plugin for MeshOutlinePlugin {
 
         app.add_plugins((
             ExtractComponentPlugin::<MeshOutline>::default(),
+            ExtractComponentPlugin::<MeshOutlines>::default(),
+            ExtractComponentPlugin::<FadingOutline>::default(),
             ExtractComponentPlugin::<OutlineCamera>::default(),
+            ExtractComponentPlugin::<OutlineOnly>::default(),
         ));
-        app.register_type::<MeshOutline>();
+        app.register_type::<MeshOutline>()
+            .register_type::<MeshOutlines>()
+            .register_type::<OutlineOcclusion>()
+            .register_type::<OutlineFalloff>()
+            .register_type::<OutlinePattern>()
+            .register_type::<OutlineNoise>()
+            .register_type::<OutlinePulse>()
+            .register_type::<OutlinePlacement>()
+            .register_type::<OutlineOffset>()
+            .register_type::<OutlineCorners>()
+            .register_type::<OutlineEdges>()
+            .register_type::<OutlineFill>()
+            .register_type::<OutlineContactLine>()
+            .register_type::<OutlineGradient>()
+            .register_type::<OutlineBlendMode>()
+            .register_type::<OutlinePriority>()
+            .register_type::<OutlineWidth>()
+            .register_type::<OutlineCameraSettings>()
+            .register_type::<OutlineSceneEdges>()
+            .register_type::<OutlineCategories>()
+            .register_type::<OutlineLayers>()
+            .register_type::<OutlineGroup>()
+            .register_type::<InheritOutline>()
+            .register_type::<InheritedOutline>()
+            .register_type::<OutlineOnly>()
+            .register_type::<OutlineTransition>()
+            .register_type::<OutlineFade>()
+            .register_type::<FadingOutline>();
+
+        app.add_observer(start_fade_in)
+            .add_observer(start_fade_out)
+            .add_observer(start_stack_fade_out)
+            .add_observer(show_outline_only_mesh)
+            .add_systems(
+                PostUpdate,
+                (propagate_outlines, advance_outline_transitions).chain(),
+            )
+            .add_systems(PostUpdate, update_normal_prepass)
+            .add_systems(
+                PostUpdate,
+                hide_outline_only_meshes.before(VisibilitySystems::CheckVisibility),
+            )
+            .add_systems(
+                PostUpdate,
+                check_outline_layer_visibility
+                    .in_set(VisibilitySystems::CheckVisibility)
+                    .after(check_visibility_cpu_culling),
+            );
 
         app.add_plugins(
             BinnedRenderPhasePlugin::<MeshOutline3d, MeshMaskPipeline>::new(
@@ -74,7 +142,10 @@ impl Plugin for MeshOutlinePlugin {
             .init_resource::<SpecializedMeshPipelines<MeshMaskPipeline>>()
             .init_resource::<ViewBinnedRenderPhases<MeshOutline3d>>()
             .init_resource::<ExtractedOutlines>()
+            .init_resource::<OutlineTieBreaks>()
             .init_resource::<OutlineBindGroups>()
+            .init_resource::<OutlineStyles>()
+            .init_resource::<OutlineViewUniforms>()
             // The mask pipeline wraps `MeshPipeline`, so build it in
             // `RenderStartup` after `MeshPipeline` has been created.
             .add_systems(
@@ -83,7 +154,12 @@ impl Plugin for MeshOutlinePlugin {
             )
             .add_systems(
                 ExtractSchedule,
-                (update_views, extract_outlines_to_resource).after(extract_skins),
+                (
+                    update_views,
+                    extract_outline_views,
+                    extract_outlines_to_resource,
+                )
+                    .after(extract_skins),
             )
             .add_systems(
                 Render,
@@ -91,8 +167,11 @@ impl Plugin for MeshOutlinePlugin {
                     queue_outline.in_set(RenderSystems::QueueMeshes),
                     (
                         prepare_flood_settings,
+                        prepare_outline_view_uniforms,
                         prepare_flood_textures,
-                        prepare_outline_bind_groups.after(prepare_flood_textures),
+                        prepare_outline_bind_groups
+                            .after(prepare_flood_textures)
+                            .after(prepare_outline_view_uniforms),
                     )
                         .in_set(RenderSystems::PrepareBindGroups),
                     // Note: batching (`batch_and_prepare_binned_render_phase`) and
@@ -118,33 +197,303 @@ impl Plugin for MeshOutlinePlugin {
         };
         render_app
             .init_resource::<JumpFloodPipeline>()
-            .init_resource::<ComposeOutputPipeline>();
+            .init_resource::<ComposeOutputPipeline>()
+            .init_resource::<SceneEdgePipeline>();
     }
 }
 
 /// Marker component for enabling a 3D camera to render mesh outlines.
-#[derive(Debug, Component, Reflect, Clone, ExtractComponent)]
-#[reflect(Component)]
+#[derive(Debug, Component, Reflect, Clone, Default, ExtractComponent)]
+#[reflect(Component, Default)]
 pub struct OutlineCamera;
 
+/// Per-camera outline settings. Cameras without this component use
+/// [`OutlineCameraSettings::default`].
+#[derive(Debug, Component, Reflect, Clone)]
+#[reflect(Component, Default)]
+#[require(OutlineCamera)]
+pub struct OutlineCameraSettings {
+    /// Whether the camera renders outlines at all.
+    pub enabled: bool,
+    /// Multiplies the resolved width of every outline.
+    pub width_scale: f32,
+    /// Caps the scaled width of every outline, in physical pixels.
+    pub max_width: Option<f32>,
+    /// Resolution of the outline textures relative to the render target.
+    /// Lower values are cheaper but give coarser outlines.
+    pub resolution_scale: f32,
+    /// Only outlines sharing at least one category with the camera are drawn.
+    pub categories: OutlineCategories,
+    /// How many overlapping outlines each pixel keeps, from 1 to
+    /// [`MAX_OVERLAP_CANDIDATES`]. With 2, the band of an outline stays visible
+    /// below the band of the next closest one where they overlap, at the cost
+    /// of a second set of flood textures. Values outside that range are
+    /// clamped, with a warning.
+    pub overlap_candidates: u32,
+    /// How overlapping outlines of different meshes are ranked.
+    pub priority: OutlinePriority,
+    /// Smooths the outer edge of outline bands, and with MSAA resolves their
+    /// occlusion against every depth sample of the pixel rather than one.
+    pub antialias: bool,
+    /// Ink lines along the edges of everything the camera sees, outlined or
+    /// not. Enabling them adds a
+    /// [`NormalPrepass`](bevy::core_pipeline::prepass::NormalPrepass) to the
+    /// camera, which is removed again when they are turned off.
+    pub scene_edges: Option<OutlineSceneEdges>,
+}
+
+/// Most overlapping outlines a pixel can keep; each one needs its own flood
+/// render target, and WebGPU limits the bytes written per sample.
+pub const MAX_OVERLAP_CANDIDATES: u32 = 2;
+
+/// Clamps an overlap candidate count into 1 to [`MAX_OVERLAP_CANDIDATES`],
+/// warning once when it was out of range.
+pub(crate) fn clamp_overlap_candidates(overlap_candidates: u32) -> u32 {
+    if !(1..=MAX_OVERLAP_CANDIDATES).contains(&overlap_candidates) {
+        bevy::log::warn_once!(
+            "overlap_candidates must be between 1 and {MAX_OVERLAP_CANDIDATES}, got {overlap_candidates}; clamping"
+        );
+    }
+    overlap_candidates.clamp(1, MAX_OVERLAP_CANDIDATES)
+}
+
+impl Default for OutlineCameraSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            width_scale: 1.0,
+            max_width: None,
+            resolution_scale: 1.0,
+            categories: OutlineCategories::ALL,
+            overlap_candidates: 1,
+            priority: OutlinePriority::default(),
+            antialias: true,
+            scene_edges: None,
+        }
+    }
+}
+
+impl OutlineCameraSettings {
+    pub fn with_enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+
+    pub fn with_width_scale(self, width_scale: f32) -> Self {
+        Self {
+            width_scale,
+            ..self
+        }
+    }
+
+    pub fn with_max_width(self, max_width: f32) -> Self {
+        Self {
+            max_width: Some(max_width),
+            ..self
+        }
+    }
+
+    pub fn with_resolution_scale(self, resolution_scale: f32) -> Self {
+        Self {
+            resolution_scale,
+            ..self
+        }
+    }
+
+    pub fn with_categories(self, categories: OutlineCategories) -> Self {
+        Self { categories, ..self }
+    }
+
+    /// Values outside 1 to [`MAX_OVERLAP_CANDIDATES`] are clamped, with a
+    /// warning.
+    pub fn with_overlap_candidates(self, overlap_candidates: u32) -> Self {
+        Self {
+            overlap_candidates: clamp_overlap_candidates(overlap_candidates),
+            ..self
+        }
+    }
+
+    pub fn with_priority(self, priority: OutlinePriority) -> Self {
+        Self { priority, ..self }
+    }
+
+    pub fn with_antialias(self, antialias: bool) -> Self {
+        Self { antialias, ..self }
+    }
+
+    pub fn with_scene_edges(self, scene_edges: OutlineSceneEdges) -> Self {
+        Self {
+            scene_edges: Some(scene_edges),
+            ..self
+        }
+    }
+}
+
+/// Screen-space edge detection over the whole scene, for a toon or blueprint
+/// look without outlining every mesh.
+///
+/// Edges are found where the depth or the normals of the depth and normal
+/// prepasses change sharply between neighboring pixels. Their lines are drawn
+/// below the camera's mesh outlines.
+///
+/// With MSAA, only the first sample of each prepass pixel is compared, so the
+/// lines are not antialiased.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct OutlineSceneEdges {
+    pub color: Color,
+    /// Width of the lines, in physical pixels.
+    pub width: f32,
+    /// Relative jump in distance from the camera between neighboring pixels
+    /// past which they lie on either side of an edge.
+    pub depth_threshold: f32,
+    /// Angle in radians between the normals of neighboring pixels past which
+    /// they lie on either side of an edge.
+    pub normal_threshold: f32,
+}
+
+impl Default for OutlineSceneEdges {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            width: 1.0,
+            depth_threshold: 0.1,
+            normal_threshold: 45f32.to_radians(),
+        }
+    }
+}
+
+impl OutlineSceneEdges {
+    pub fn with_color(self, color: Color) -> Self {
+        Self { color, ..self }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self { width, ..self }
+    }
+
+    pub fn with_depth_threshold(self, depth_threshold: f32) -> Self {
+        Self {
+            depth_threshold,
+            ..self
+        }
+    }
+
+    pub fn with_normal_threshold(self, normal_threshold: f32) -> Self {
+        Self {
+            normal_threshold,
+            ..self
+        }
+    }
+}
+
+/// A set of up to 32 outline categories, used to choose which cameras draw
+/// which outlines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct OutlineCategories(pub u32);
+
+impl OutlineCategories {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// A set containing only category `n`, which must be below 32.
+    pub const fn category(n: u32) -> Self {
+        Self(1 << n)
+    }
+
+    /// Adds category `n` to the set.
+    pub const fn with(self, n: u32) -> Self {
+        Self(self.0 | (1 << n))
+    }
+
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+/// Outlines belong to category 0 unless configured otherwise.
+impl Default for OutlineCategories {
+    fn default() -> Self {
+        Self::category(0)
+    }
+}
+
+/// Render layers used for outlines instead of mesh visibility.
+///
+/// On an outlined entity, the outline is drawn by the cameras whose outline
+/// layers intersect these, whether or not they see the mesh itself, e.g. an
+/// overlay camera whose [`RenderLayers`] leave the mesh out. The mesh is
+/// still frustum culled by its `Aabb`. Without it, every camera that sees the
+/// mesh draws the outline.
+///
+/// On a camera, it overrides the camera's [`RenderLayers`] as its outline
+/// layers.
+#[derive(Debug, Component, Reflect, Clone, Default, PartialEq, Deref, DerefMut)]
+#[reflect(Component, Default)]
+pub struct OutlineLayers(pub RenderLayers);
+
 /// Adds a mesh outline effect to entity.
 /// Should be added to the entity containing the Mesh3d component.
 #[derive(Debug, Component, Reflect, Clone)]
 #[reflect(Component)]
 pub struct MeshOutline {
+    /// Values below 1.0 fade the outline out, values above 1.0 brighten its
+    /// color (e.g. for HDR bloom).
     pub intensity: f32,
-    pub width: f32,
-    pub priority: f32,
+    pub width: OutlineWidth,
+    /// Lower bound of the resolved width, in physical pixels.
+    pub min_width: Option<f32>,
+    /// Upper bound of the resolved width, in physical pixels.
+    pub max_width: Option<f32>,
+    /// Where the outlines of different meshes overlap, the one with the
+    /// higher priority wins; see [`OutlinePriority`]. Must stay within
+    /// ±2^24, which the GPU represents exactly.
+    pub priority: i32,
     pub color: Color,
+    pub occlusion: OutlineOcclusion,
+    pub falloff: OutlineFalloff,
+    pub pattern: OutlinePattern,
+    /// Makes the band wobble like a hand-drawn line.
+    pub noise: Option<OutlineNoise>,
+    /// Animates the width and color on the GPU, replacing `width`.
+    pub pulse: Option<OutlinePulse>,
+    pub placement: OutlinePlacement,
+    /// Displaces the band from the silhouette, e.g. for a drop shadow.
+    pub offset: Option<OutlineOffset>,
+    pub corners: OutlineCorners,
+    /// Which edges of the mesh are outlined, besides its silhouette.
+    pub edges: OutlineEdges,
+    /// Varies the color across the band, replacing `color`.
+    pub gradient: Option<OutlineGradient>,
+    pub fill: Option<OutlineFill>,
+    /// Draws a line where the mesh cuts through other geometry.
+    pub contact_line: Option<OutlineContactLine>,
+    pub blend_mode: OutlineBlendMode,
+    /// Categories of the outline; see [`OutlineCameraSettings::categories`].
+    pub categories: OutlineCategories,
 }
 
 impl MeshOutline {
-    pub fn new(width: f32) -> Self {
+    pub fn new(width: impl Into<OutlineWidth>) -> Self {
         Self {
             intensity: 1.0,
-            width,
-            priority: 0.0,
+            width: width.into(),
+            min_width: None,
+            max_width: None,
+            priority: 0,
             color: Color::BLACK,
+            occlusion: OutlineOcclusion::Visible,
+            falloff: OutlineFalloff::Solid,
+            pattern: OutlinePattern::Solid,
+            noise: None,
+            pulse: None,
+            placement: OutlinePlacement::Outer,
+            offset: None,
+            corners: OutlineCorners::Round,
+            edges: OutlineEdges::default(),
+            gradient: None,
+            fill: None,
+            contact_line: None,
+            blend_mode: OutlineBlendMode::Alpha,
+            categories: OutlineCategories::default(),
         }
     }
 
@@ -152,54 +501,1269 @@ impl MeshOutline {
         Self { intensity, ..self }
     }
 
-    pub fn with_priority(self, priority: f32) -> Self {
+    pub fn with_min_width(self, min_width: f32) -> Self {
+        Self {
+            min_width: Some(min_width),
+            ..self
+        }
+    }
+
+    pub fn with_max_width(self, max_width: f32) -> Self {
+        Self {
+            max_width: Some(max_width),
+            ..self
+        }
+    }
+
+    pub fn with_priority(self, priority: i32) -> Self {
         Self { priority, ..self }
     }
 
     pub fn with_color(self, color: Color) -> Self {
         Self { color, ..self }
     }
+
+    pub fn with_occlusion(self, occlusion: OutlineOcclusion) -> Self {
+        Self { occlusion, ..self }
+    }
+
+    pub fn with_falloff(self, falloff: OutlineFalloff) -> Self {
+        Self { falloff, ..self }
+    }
+
+    pub fn with_pattern(self, pattern: OutlinePattern) -> Self {
+        Self { pattern, ..self }
+    }
+
+    pub fn with_noise(self, noise: OutlineNoise) -> Self {
+        Self {
+            noise: Some(noise),
+            ..self
+        }
+    }
+
+    pub fn with_pulse(self, pulse: OutlinePulse) -> Self {
+        Self {
+            pulse: Some(pulse),
+            ..self
+        }
+    }
+
+    pub fn with_placement(self, placement: OutlinePlacement) -> Self {
+        Self { placement, ..self }
+    }
+
+    pub fn with_offset(self, offset: OutlineOffset) -> Self {
+        Self {
+            offset: Some(offset),
+            ..self
+        }
+    }
+
+    pub fn with_corners(self, corners: OutlineCorners) -> Self {
+        Self { corners, ..self }
+    }
+
+    pub fn with_edges(self, edges: OutlineEdges) -> Self {
+        Self { edges, ..self }
+    }
+
+    pub fn with_gradient(self, gradient: OutlineGradient) -> Self {
+        Self {
+            gradient: Some(gradient),
+            ..self
+        }
+    }
+
+    pub fn with_fill(self, fill: OutlineFill) -> Self {
+        Self {
+            fill: Some(fill),
+            ..self
+        }
+    }
+
+    pub fn with_contact_line(self, contact_line: OutlineContactLine) -> Self {
+        Self {
+            contact_line: Some(contact_line),
+            ..self
+        }
+    }
+
+    pub fn with_blend_mode(self, blend_mode: OutlineBlendMode) -> Self {
+        Self { blend_mode, ..self }
+    }
+
+    pub fn with_categories(self, categories: OutlineCategories) -> Self {
+        Self { categories, ..self }
+    }
+}
+
+/// Maximum number of layers drawn from a [`MeshOutlines`] stack.
+pub const MAX_OUTLINE_LAYERS: usize = 8;
+
+/// Several outlines stacked on one mesh, e.g. a thin ink line over a wide
+/// colored halo. Replaces [`MeshOutline`] on entities that have both.
+///
+/// Layers are drawn in order, each over the previous ones, and share the
+/// mesh's silhouette. The priority and categories of the first layer apply to
+/// the whole stack. Up to [`MAX_OUTLINE_LAYERS`] layers are drawn.
+#[derive(Debug, Component, Reflect, Clone, Default)]
+#[reflect(Component, Default)]
+pub struct MeshOutlines(pub Vec<MeshOutline>);
+
+impl MeshOutlines {
+    pub fn new(layers: impl IntoIterator<Item = MeshOutline>) -> Self {
+        Self(layers.into_iter().collect())
+    }
+
+    /// Adds a layer drawn over the existing ones.
+    pub fn with_layer(mut self, layer: MeshOutline) -> Self {
+        self.0.push(layer);
+        self
+    }
+}
+
+/// Merges the outlines of all meshes in the same group into a single
+/// silhouette, with no outline where they touch or overlap. Meshes in
+/// different groups, or without one, still outline each other.
+///
+/// Ids above [`MAX_OUTLINE_GROUP`] are ignored, with a warning, and the mesh is
+/// outlined on its own.
+#[derive(Debug, Component, Reflect, Clone, Copy, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct OutlineGroup(pub u32);
+
+/// Largest [`OutlineGroup`] id. Groups are told apart on the GPU as floats,
+/// which count exactly up to 2^24, and id zero is shifted to one there.
+pub const MAX_OUTLINE_GROUP: u32 = (1 << 24) - 1;
+
+impl OutlineGroup {
+    /// The group's id, or `None` when it is above [`MAX_OUTLINE_GROUP`],
+    /// warning once.
+    pub(crate) fn id(&self) -> Option<u32> {
+        if self.0 > MAX_OUTLINE_GROUP {
+            bevy::log::warn_once!(
+                "OutlineGroup ids must be at most {MAX_OUTLINE_GROUP}, got {}; ignoring the group",
+                self.0
+            );
+            return None;
+        }
+        Some(self.0)
+    }
+}
+
+/// Applies the entity's [`MeshOutline`] to all of its descendant meshes, such
+/// as the meshes of a glTF scene, including ones spawned later. Descendants
+/// with their own outline keep it.
+#[derive(Debug, Component, Reflect, Clone, Default)]
+#[reflect(Component, Default)]
+pub struct InheritOutline;
+
+/// Marks a [`MeshOutline`] copied from the [`InheritOutline`] ancestor
+/// `source`. It is kept in sync with the source and removed along with it.
+#[derive(Debug, Component, Reflect, Clone)]
+#[reflect(Component)]
+pub struct InheritedOutline {
+    pub source: Entity,
+}
+
+/// Draws the entity's outline without its mesh, e.g. for placement previews
+/// or markers pointing out hidden items.
+///
+/// The mesh is moved to its own visibility class, which keeps it out of the
+/// main and prepass passes, and gets [`NotShadowCaster`](bevy::light::NotShadowCaster)
+/// unless it already has one. It is still frustum culled by its `Aabb` like
+/// any other mesh and must stay visible: `Visibility::Hidden` hides the
+/// outline too.
+#[derive(Debug, Component, Reflect, Clone, Default, ExtractComponent)]
+#[reflect(Component, Default)]
+pub struct OutlineOnly;
+
+/// Fades the entity's outline in when [`MeshOutline`] or [`MeshOutlines`] is
+/// inserted, and keeps drawing it while it fades out after being removed.
+///
+/// Both the opacity and the width of the outline are ramped. The fade is
+/// animated on the GPU like [`OutlinePulse`], so the outline's appearance
+/// only changes when a fade starts, turns around or ends. Despawning the
+/// entity drops its outline right away.
+#[derive(Debug, Component, Reflect, Clone, Copy, PartialEq)]
+#[reflect(Component)]
+pub struct OutlineTransition {
+    /// Seconds to fade in.
+    pub fade_in: f32,
+    /// Seconds to fade out.
+    pub fade_out: f32,
+    /// Maps the elapsed fraction of a fade to the outline's visibility.
+    pub easing: EaseFunction,
+}
+
+impl OutlineTransition {
+    pub fn new(fade_in: f32, fade_out: f32) -> Self {
+        Self {
+            fade_in,
+            fade_out,
+            easing: EaseFunction::Linear,
+        }
+    }
+
+    pub fn with_easing(self, easing: EaseFunction) -> Self {
+        Self { easing, ..self }
+    }
+}
+
+/// How far the outline of an entity with an [`OutlineTransition`] has faded
+/// in. Added and updated by the plugin.
+#[derive(Debug, Component, Reflect, Clone, Copy, PartialEq)]
+#[reflect(Component, Default)]
+pub struct OutlineFade {
+    /// Elapsed fraction of the fade, from 0.0 (hidden) to 1.0 (fully shown).
+    pub progress: f32,
+    /// `progress` after easing, scaling the outline's opacity and width.
+    pub visibility: f32,
+    /// Wrapped elapsed seconds when the running fade started, like the time
+    /// the outline shaders animate with, and `progress` then.
+    pub(crate) start_time: f32,
+    pub(crate) start_progress: f32,
+    /// Change of `progress` per second, zero when not fading.
+    pub(crate) rate: f32,
+    pub(crate) easing: EaseFunction,
+}
+
+impl Default for OutlineFade {
+    fn default() -> Self {
+        Self {
+            progress: 0.0,
+            visibility: 0.0,
+            start_time: 0.0,
+            start_progress: 0.0,
+            rate: 0.0,
+            easing: EaseFunction::Linear,
+        }
+    }
+}
+
+impl OutlineFade {
+    pub const VISIBLE: Self = Self {
+        progress: 1.0,
+        visibility: 1.0,
+        start_time: 0.0,
+        start_progress: 1.0,
+        rate: 0.0,
+        easing: EaseFunction::Linear,
+    };
+
+    /// Whether the fade still changes the outline.
+    fn is_running(&self) -> bool {
+        self.rate != 0.0 || self.progress < 1.0
+    }
+
+    /// `start_time`, `start_progress` and `rate` of a running fade, and its
+    /// easing curve sampled at evenly spaced progress. An outline without one
+    /// stays fully visible.
+    fn gpu_params(fade: Option<&Self>) -> (Vec3, [f32; FADE_CURVE_SAMPLES]) {
+        match fade.filter(|fade| fade.is_running()) {
+            Some(fade) => (
+                Vec3::new(fade.start_time, fade.start_progress, fade.rate),
+                std::array::from_fn(|i| {
+                    fade.easing
+                        .sample_clamped(i as f32 / (FADE_CURVE_SAMPLES - 1) as f32)
+                }),
+            ),
+            None => (Vec3::ZERO, [1.0; FADE_CURVE_SAMPLES]),
+        }
+    }
+}
+
+/// Number of samples of the easing curve of a fade the GPU animates outlines
+/// with; it interpolates linearly between them.
+pub(crate) const FADE_CURVE_SAMPLES: usize = 16;
+
+/// The outline layers of an entity whose [`MeshOutline`] or [`MeshOutlines`]
+/// was removed, drawn until they have faded out. Added and removed by the
+/// plugin.
+#[derive(Debug, Component, Reflect, Clone)]
+#[reflect(Component)]
+pub struct FadingOutline {
+    pub layers: Vec<MeshOutline>,
+    pub transition: OutlineTransition,
+}
+
+/// How an outline is composited over the scene.
+///
+/// The outline's opacity is its color's alpha multiplied by
+/// [`MeshOutline::intensity`] clamped to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum OutlineBlendMode {
+    /// Blends the outline over the scene by its opacity.
+    #[default]
+    Alpha,
+    /// Replaces the scene color, ignoring the outline's opacity.
+    Replace,
+    /// Adds the outline color to the scene, scaled by its opacity.
+    Additive,
+    /// Multiplies the scene by the outline color, darkening it.
+    Multiply,
+    /// Inverse of multiply, brightening the scene.
+    Screen,
+}
+
+impl OutlineBlendMode {
+    /// Mode index understood by `compose_output.wgsl`.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlineBlendMode::Alpha => 0,
+            OutlineBlendMode::Replace => 1,
+            OutlineBlendMode::Additive => 2,
+            OutlineBlendMode::Multiply => 3,
+            OutlineBlendMode::Screen => 4,
+        }
+    }
+}
+
+/// How a camera ranks the outlines of different meshes where they overlap,
+/// from [`MeshOutline::priority`] and the depth of the outlined surface.
+///
+/// Whatever the mode, outlines that still tie go to [`OutlineGroup`]s, the
+/// highest group first, and then to the mesh whose [`Entity`] comes first, so
+/// they don't flicker from frame to frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub enum OutlinePriority {
+    /// The closest surface wins, and priority only settles equal depths.
+    DepthFirst,
+    /// The highest priority wins, and the closest surface among equal
+    /// priorities.
+    #[default]
+    PriorityFirst,
+    /// Ranks outlines by their priority plus their depth times the weight.
+    /// Depth goes from 0.0 far away to 1.0 at the near plane, so the weight
+    /// is the most priority that being closer can make up for.
+    Blend(f32),
+}
+
+impl OutlinePriority {
+    /// Mode index understood by `flood.wgsl`.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlinePriority::DepthFirst => 0,
+            OutlinePriority::PriorityFirst => 1,
+            OutlinePriority::Blend(_) => 2,
+        }
+    }
+
+    /// Weight of the depth in [`OutlinePriority::Blend`], zero otherwise.
+    pub(crate) fn depth_weight(&self) -> f32 {
+        match self {
+            OutlinePriority::Blend(weight) => *weight,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Most stops an [`OutlineGradient`] can have; later ones are ignored.
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// Outline color that varies with the distance from the silhouette.
+///
+/// Each stop is an offset from 0.0 at the silhouette to 1.0 at the outer edge
+/// of the band, and the color there. Colors are interpolated linearly between
+/// stops and held past the first and last one. Like [`MeshOutline::color`],
+/// they are scaled by [`MeshOutline::intensity`].
+#[derive(Debug, Clone, PartialEq, Default, Reflect)]
+pub struct OutlineGradient {
+    pub stops: Vec<(f32, Color)>,
+}
+
+impl OutlineGradient {
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        Self {
+            stops: stops.into_iter().collect(),
+        }
+    }
+
+    /// Samples the first row of a ramp image into evenly spaced stops, one
+    /// per pixel. Images wider than [`MAX_GRADIENT_STOPS`] pixels are sampled
+    /// at that many evenly spaced pixels, so finer detail in the ramp is lost.
+    /// The image must be readable on the CPU, e.g. loaded with
+    /// `RenderAssetUsages::MAIN_WORLD`.
+    pub fn from_image(image: &Image) -> Self {
+        let width = image.width().max(1);
+        let count = (width as usize).min(MAX_GRADIENT_STOPS);
+        let last = (count - 1).max(1) as f32;
+        Self::new((0..count).map(|i| {
+            let offset = i as f32 / last;
+            let x = (offset * (width - 1) as f32).round() as u32;
+            (offset, image.get_color_at(x, 0).unwrap_or(Color::NONE))
+        }))
+    }
+
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        self.stops.push((offset, color));
+        self
+    }
+
+    /// Color at normalized distance `t` across the band, before intensity is
+    /// applied, or `None` without stops. Must match `band_color` in
+    /// `compose_output.wgsl`.
+    pub fn color_at(&self, t: f32) -> Option<LinearRgba> {
+        let stops = extract_gradient(self);
+        let mut color = stops.first()?.1;
+        for pair in stops.windows(2) {
+            let [(start, from), (end, to)] = [pair[0], pair[1]];
+            if t >= end {
+                color = to;
+                continue;
+            }
+            if t > start {
+                color = from.lerp(to, (t - start) / (end - start).max(1e-6));
+            }
+            break;
+        }
+        Some(LinearRgba::from_vec4(color))
+    }
+}
+
+/// Tints the outlined mesh's own pixels, in addition to drawing the outline
+/// around them.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct OutlineFill {
+    pub color: Color,
+    /// Opacity of the tint, from 0.0 (invisible) to 1.0 (solid `color`).
+    pub opacity: f32,
+    pub occlusion: OutlineOcclusion,
+}
+
+impl OutlineFill {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            opacity: 0.5,
+            occlusion: OutlineOcclusion::Visible,
+        }
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self { opacity, ..self }
+    }
+
+    pub fn with_occlusion(self, occlusion: OutlineOcclusion) -> Self {
+        Self { occlusion, ..self }
+    }
+}
+
+/// A line along the intersection of the outlined mesh with other geometry,
+/// e.g. where it sinks into terrain or water, which the outline itself stops
+/// at.
+///
+/// It is drawn where parts of the mesh in front of the scene meet parts just
+/// behind it, centered on the intersection.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct OutlineContactLine {
+    pub color: Color,
+    /// Width of the line, in physical pixels.
+    pub width: f32,
+    /// How far behind the scene, relative to its distance from the camera,
+    /// the mesh can be and still touch it. Larger values also draw the line
+    /// where the mesh is hidden by geometry just in front of it.
+    pub epsilon: f32,
+}
+
+impl OutlineContactLine {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            width: 2.0,
+            epsilon: 0.02,
+        }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self { width, ..self }
+    }
+
+    pub fn with_epsilon(self, epsilon: f32) -> Self {
+        Self { epsilon, ..self }
+    }
+}
+
+/// Which side of the mesh's silhouette edge the outline is drawn on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum OutlinePlacement {
+    /// Outside the silhouette.
+    #[default]
+    Outer,
+    /// Inside the silhouette, confined to the mesh's own pixels.
+    Inner,
+    /// Straddling the silhouette edge, half of the width on each side.
+    Centered,
+}
+
+impl OutlinePlacement {
+    /// Mode index understood by the flood and compose shaders.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlinePlacement::Outer => 0,
+            OutlinePlacement::Inner => 1,
+            OutlinePlacement::Centered => 2,
+        }
+    }
+
+    /// Width of the band on the `inside` or the outside of the silhouette,
+    /// for an outline `width` wide. Must match the sides `compose_output.wgsl`
+    /// draws, and the halved width of centered outlines.
+    pub fn band_width(&self, width: f32, inside: bool) -> f32 {
+        match (self, inside) {
+            (OutlinePlacement::Centered, _) => width * 0.5,
+            (OutlinePlacement::Outer, false) | (OutlinePlacement::Inner, true) => width,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Displaces the outline band from the silhouette on screen, for a cheap drop
+/// shadow or an extruded sticker look.
+///
+/// The band is drawn as if around the silhouette moved by `screen_offset`,
+/// and is still kept outside (or for inner outlines, inside) the mesh's own
+/// pixels. Outer bands cover the moved silhouette too, so they read as a
+/// shadow cast behind the mesh. The flood has to reach past the offset,
+/// which takes more passes and lets the outline win more pixels from the
+/// outlines of other meshes.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub struct OutlineOffset {
+    /// Offset in physical pixels, with +y pointing down the screen.
+    pub screen_offset: Vec2,
+    /// Width in physical pixels over which the outer edge of the band fades
+    /// out.
+    pub blur: f32,
+}
+
+impl OutlineOffset {
+    pub fn new(screen_offset: Vec2) -> Self {
+        Self {
+            screen_offset,
+            blur: 0.0,
+        }
+    }
+
+    pub fn with_blur(self, blur: f32) -> Self {
+        Self { blur, ..self }
+    }
+}
+
+/// Relative jump in view depth between neighboring pixels of a mesh past
+/// which one part of it is in front of another.
+const DEPTH_DISCONTINUITY: f32 = 0.03;
+
+/// Edges of a mesh its outline is drawn along.
+///
+/// Interior lines, along creases and where the mesh overlaps itself, are drawn
+/// on the mesh, centered on the edge and as wide as the outline in total. They
+/// are found from the normals and depths of the outlined meshes, so meshes
+/// without normals have no creases.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct OutlineEdges {
+    /// The outer silhouette, the only edge outlined by default.
+    pub silhouette: bool,
+    /// Edges where faces meet at more than `crease_angle`.
+    pub creases: bool,
+    /// Angle in radians between the normals of neighboring pixels past which
+    /// they lie on either side of a crease.
+    pub crease_angle: f32,
+    /// Edges where one part of the mesh is in front of another, e.g. an arm in
+    /// front of the torso.
+    pub depth_discontinuity: bool,
+}
+
+impl Default for OutlineEdges {
+    fn default() -> Self {
+        Self {
+            silhouette: true,
+            creases: false,
+            crease_angle: 30f32.to_radians(),
+            depth_discontinuity: false,
+        }
+    }
+}
+
+impl OutlineEdges {
+    pub fn with_silhouette(self, silhouette: bool) -> Self {
+        Self { silhouette, ..self }
+    }
+
+    pub fn with_creases(self, creases: bool) -> Self {
+        Self { creases, ..self }
+    }
+
+    pub fn with_crease_angle(self, crease_angle: f32) -> Self {
+        Self {
+            crease_angle,
+            ..self
+        }
+    }
+
+    pub fn with_depth_discontinuity(self, depth_discontinuity: bool) -> Self {
+        Self {
+            depth_discontinuity,
+            ..self
+        }
+    }
+
+    /// Edge flags understood by `types.wgsl`.
+    pub(crate) fn flags(&self) -> u32 {
+        (self.silhouette as u32)
+            | ((self.creases as u32) << 1)
+            | ((self.depth_discontinuity as u32) << 2)
+    }
+
+    /// Whether an interior edge runs between `center` and `neighbor`, two
+    /// neighboring pixels of the same silhouette, each with its world normal
+    /// in `xyz` and its view depth in `w`. Must match the neighbor test of
+    /// `is_interior_edge` in `types.wgsl`.
+    pub fn separates(&self, center: Vec4, neighbor: Vec4) -> bool {
+        if center.w <= 0.0 || neighbor.w <= 0.0 {
+            return false;
+        }
+        let (center_normal, neighbor_normal) = (center.truncate(), neighbor.truncate());
+        // Meshes without normals leave them zero.
+        let has_normals =
+            center_normal.length_squared() > 0.5 && neighbor_normal.length_squared() > 0.5;
+        (self.creases
+            && has_normals
+            && center_normal.dot(neighbor_normal) < self.crease_angle.cos())
+            || (self.depth_discontinuity && neighbor.w - center.w > DEPTH_DISCONTINUITY * center.w)
+    }
+}
+
+/// Shape of the outline around the corners of the silhouette, set by how the
+/// distance to it is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum OutlineCorners {
+    /// Euclidean distance, rounding the outline's corners.
+    #[default]
+    Round,
+    /// Chebyshev distance, keeping the corners of boxy silhouettes sharp.
+    Square,
+    /// Manhattan distance, beveling the corners at 45 degrees.
+    Diamond,
+}
+
+impl OutlineCorners {
+    /// Mode index understood by the flood and compose shaders.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlineCorners::Round => 0,
+            OutlineCorners::Square => 1,
+            OutlineCorners::Diamond => 2,
+        }
+    }
+
+    /// Distance in pixels spanned by `delta`, as the outline band measures it.
+    /// Must match `corner_distance` in `types.wgsl`.
+    pub fn distance(&self, delta: Vec2) -> f32 {
+        let delta = delta.abs();
+        match self {
+            OutlineCorners::Round => delta.length(),
+            OutlineCorners::Square => delta.max_element(),
+            OutlineCorners::Diamond => delta.x + delta.y,
+        }
+    }
+}
+
+/// Width of an outline, in one of several units.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub enum OutlineWidth {
+    /// Physical pixels of the render target.
+    Pixels(f32),
+    /// Logical pixels, scaled by the render target's scale factor.
+    LogicalPixels(f32),
+    /// World units at the depth of the entity's origin, so the outline gets
+    /// thinner with distance like the mesh itself. Origins closer than the
+    /// camera's near plane, or behind the camera, count as on the near plane.
+    World(f32),
+    /// Fraction of the viewport height.
+    ScreenFraction(f32),
+}
+
+impl OutlineWidth {
+    /// The width in this variant's unit.
+    pub fn value(&self) -> f32 {
+        match *self {
+            OutlineWidth::Pixels(width)
+            | OutlineWidth::LogicalPixels(width)
+            | OutlineWidth::World(width)
+            | OutlineWidth::ScreenFraction(width) => width,
+        }
+    }
+
+    /// Unit index understood by the mask and flood shaders.
+    pub(crate) fn unit(&self) -> u32 {
+        match self {
+            OutlineWidth::Pixels(_) => 0,
+            OutlineWidth::LogicalPixels(_) => 1,
+            OutlineWidth::World(_) => 2,
+            OutlineWidth::ScreenFraction(_) => 3,
+        }
+    }
+}
+
+impl From<f32> for OutlineWidth {
+    fn from(width: f32) -> Self {
+        OutlineWidth::Pixels(width)
+    }
+}
+
+/// How an outline fades out from the silhouette towards its outer edge.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub enum OutlineFalloff {
+    /// A hard-edged band of constant opacity.
+    #[default]
+    Solid,
+    /// Fades linearly to transparent at the outer edge.
+    Linear,
+    /// Fades along a smoothstep curve, giving soft inner and outer edges.
+    Smoothstep,
+    /// Fades exponentially; a higher `power` concentrates the outline closer
+    /// to the silhouette. `power` should be greater than zero.
+    Exponential { power: f32 },
+}
+
+impl OutlineFalloff {
+    /// Mode index understood by `compose_output.wgsl`.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlineFalloff::Solid => 0,
+            OutlineFalloff::Linear => 1,
+            OutlineFalloff::Smoothstep => 2,
+            OutlineFalloff::Exponential { .. } => 3,
+        }
+    }
+
+    pub(crate) fn power(&self) -> f32 {
+        match self {
+            OutlineFalloff::Exponential { power } => *power,
+            _ => 0.0,
+        }
+    }
+
+    /// Opacity of the band at `t`, its distance from the silhouette as a
+    /// fraction of its width. Must match `falloff_alpha` in
+    /// `compose_output.wgsl`.
+    pub fn opacity(&self, t: f32) -> f32 {
+        match self {
+            OutlineFalloff::Solid => 1.0,
+            OutlineFalloff::Linear => 1.0 - t,
+            OutlineFalloff::Smoothstep => {
+                let t = t.clamp(0.0, 1.0);
+                1.0 - t * t * (3.0 - 2.0 * t)
+            }
+            OutlineFalloff::Exponential { power } => {
+                // Rescaled so the curve still reaches zero at the outer edge.
+                let power = power.max(1e-4);
+                let edge = (-power).exp();
+                ((-power * t).exp() - edge) / (1.0 - edge)
+            }
+        }
+    }
+}
+
+/// Breaks the outline up into dashes along the silhouette.
+///
+/// Dashes are laid out in screen space from the nearest point of the
+/// silhouette, so they cut straight across the band and keep their length
+/// along straight edges.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub enum OutlinePattern {
+    /// An unbroken outline.
+    #[default]
+    Solid,
+    /// Dashes `dash` physical pixels long, `gap` pixels apart, moving along
+    /// the outline at `speed` pixels per second ("marching ants"). The
+    /// animation runs on the GPU, so the outline doesn't change every frame.
+    Dashed { dash: f32, gap: f32, speed: f32 },
+    /// Still, square dots as long as the band is wide.
+    Dotted,
+}
+
+impl OutlinePattern {
+    /// Mode index understood by `compose_output.wgsl`.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlinePattern::Solid => 0,
+            OutlinePattern::Dashed { .. } => 1,
+            OutlinePattern::Dotted => 2,
+        }
+    }
+
+    /// Dash length, gap and speed of [`OutlinePattern::Dashed`], zero otherwise.
+    pub(crate) fn dashes(&self) -> Vec3 {
+        match self {
+            OutlinePattern::Dashed { dash, gap, speed } => Vec3::new(*dash, *gap, *speed),
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// Whether the pattern draws a band `width` pixels wide at `position`
+    /// pixels along the outline, `time` seconds in. Must match
+    /// `pattern_coverage` in `compose_output.wgsl` without anti-aliasing and
+    /// at a resolution scale of 1.
+    pub fn draws(&self, position: f32, width: f32, time: f32) -> bool {
+        let (dash, gap, offset) = match *self {
+            OutlinePattern::Solid => return true,
+            OutlinePattern::Dashed { dash, gap, speed } => (dash, gap, time * speed),
+            OutlinePattern::Dotted => (width, width, 0.0),
+        };
+        let period = dash + gap;
+        if period <= 0.0 {
+            return true;
+        }
+        let phase = ((position - offset) / period).rem_euclid(1.0) * period;
+        phase < dash && phase.min(dash - phase) > 0.0
+    }
+}
+
+/// Distorts an outline with animated noise, so its width and edge wobble like
+/// ink.
+///
+/// The noise lies in screen space, so it moves smoothly rather than popping
+/// when the camera or mesh moves.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct OutlineNoise {
+    /// Most the band grows or shrinks by, in physical pixels.
+    pub amplitude: f32,
+    /// Wobbles per physical pixel along the screen, e.g. 0.05 for one every
+    /// 20 pixels.
+    pub frequency: f32,
+    /// How fast the noise changes, in wobbles per second. Zero keeps it still.
+    pub speed: f32,
+}
+
+impl OutlineNoise {
+    pub fn new(amplitude: f32, frequency: f32) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            speed: 0.0,
+        }
+    }
+
+    pub fn with_speed(self, speed: f32) -> Self {
+        Self { speed, ..self }
+    }
+
+    /// Amplitude, frequency and speed.
+    pub(crate) fn params(&self) -> Vec3 {
+        Vec3::new(self.amplitude, self.frequency, self.speed)
+    }
+}
+
+/// Makes an outline pulse, evaluated on the GPU from the time.
+///
+/// Animating [`MeshOutline`] itself changes its appearance every frame, which
+/// rebuilds its GPU resources and splits it from the batches of identical
+/// outlines. A pulse is part of the appearance instead, so it costs nothing on
+/// the CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct OutlinePulse {
+    /// Width at the start of each period, in the unit of [`MeshOutline::width`].
+    pub min_width: f32,
+    /// Width halfway through each period.
+    pub max_width: f32,
+    /// Color halfway through each period, fading back to the outline's color.
+    /// The color stays put without one.
+    pub color_b: Option<Color>,
+    /// Seconds per pulse. Must be greater than zero.
+    pub period: f32,
+}
+
+impl OutlinePulse {
+    pub fn new(min_width: f32, max_width: f32, period: f32) -> Self {
+        Self {
+            min_width,
+            max_width,
+            color_b: None,
+            period,
+        }
+    }
+
+    pub fn with_color_b(self, color_b: Color) -> Self {
+        Self {
+            color_b: Some(color_b),
+            ..self
+        }
+    }
+
+    /// How far through the pulse it is `time` seconds in, from 0.0 at the
+    /// start of each period to 1.0 halfway through. Must match `pulse_phase`
+    /// in `types.wgsl`.
+    pub fn phase(&self, time: f32) -> f32 {
+        0.5 - 0.5 * (std::f32::consts::TAU * time / self.period).cos()
+    }
+
+    /// Width of the outline `time` seconds in.
+    pub fn width_at(&self, time: f32) -> f32 {
+        self.min_width.lerp(self.max_width, self.phase(time))
+    }
+}
+
+/// How an outline is affected by scene geometry in front of it.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub enum OutlineOcclusion {
+    /// Only draw the outline where it is not hidden by other geometry.
+    #[default]
+    Visible,
+    /// Draw the outline on top of everything, ignoring the scene depth.
+    AlwaysOnTop,
+    /// Draw the visible parts of the outline as usual, and the parts of the
+    /// mesh and its outline that are hidden behind other geometry in `color`.
+    XRay { color: Color },
+}
+
+impl OutlineOcclusion {
+    /// Mode index understood by `compose_output.wgsl`.
+    pub(crate) fn mode(&self) -> u32 {
+        match self {
+            OutlineOcclusion::Visible => 0,
+            OutlineOcclusion::AlwaysOnTop => 1,
+            OutlineOcclusion::XRay { .. } => 2,
+        }
+    }
+
+    /// Color used for the occluded parts, transparent for modes without one.
+    pub(crate) fn occluded_color(&self) -> Color {
+        match self {
+            OutlineOcclusion::XRay { color } => *color,
+            _ => Color::NONE,
+        }
+    }
 }
 
 #[derive(Debug, Component, Reflect, Clone, PartialEq)]
 pub struct ExtractedOutline {
     pub intensity: f32,
-    pub width: f32,
-    pub priority: f32,
+    pub width: OutlineWidth,
+    pub min_width: f32,
+    pub max_width: f32,
+    pub priority: i32,
     pub color: Vec4,
+    pub occlusion: u32,
+    pub occluded_color: Vec4,
+    pub falloff: u32,
+    pub falloff_power: f32,
+    pub pattern: u32,
+    /// Dash length, gap and speed of a dashed pattern.
+    pub dashes: Vec3,
+    /// Amplitude, frequency and speed of the noise, zero without one.
+    pub noise: Vec3,
+    /// Smallest and largest width of the pulse.
+    pub pulse_width: Vec2,
+    /// Linear color halfway through the pulse, with a negative alpha when the
+    /// pulse keeps the outline color.
+    pub pulse_color: Vec4,
+    /// Seconds per pulse, zero without one.
+    pub pulse_period: f32,
+    pub placement: u32,
+    /// Screen offset of the band in physical pixels, zero without one.
+    pub offset: Vec2,
+    pub offset_blur: f32,
+    pub corners: u32,
+    /// [`OutlineEdges`] flags.
+    pub edges: u32,
+    pub crease_angle: f32,
+    /// Gradient stops as (offset, linear color), sorted by offset. Empty
+    /// without a gradient.
+    pub gradient: Vec<(f32, Vec4)>,
+    /// Fill color, with the fill opacity in `w` (zero without a fill).
+    pub fill_color: Vec4,
+    pub fill_occlusion: u32,
+    pub fill_occluded_color: Vec4,
+    /// Linear color of the contact line, transparent without one.
+    pub contact_color: Vec4,
+    pub contact_width: f32,
+    pub contact_epsilon: f32,
+    pub blend_mode: u32,
+    pub categories: OutlineCategories,
+    /// The entity's [`OutlineLayers`], if any.
+    pub layers: Option<RenderLayers>,
+    pub group: Option<u32>,
+    /// Start time, starting progress and rate of the [`OutlineFade`] the GPU
+    /// animates the outline with, zero without one.
+    pub fade: Vec3,
+    /// Easing curve of the fade, one without one.
+    pub fade_curve: [f32; FADE_CURVE_SAMPLES],
+    /// The next layer of a [`MeshOutlines`] stack, drawn over this one. Only
+    /// the appearance of the layers after the first is used.
+    #[reflect(ignore)]
+    pub next_layer: Option<Box<ExtractedOutline>>,
     pub world_from_local: [Vec4; 3],
 }
 
+/// A plain, fully opaque white outline, for tests to override fields of.
+#[cfg(test)]
+impl Default for ExtractedOutline {
+    fn default() -> Self {
+        ExtractedOutline {
+            intensity: 1.0,
+            width: OutlineWidth::Pixels(0.0),
+            min_width: 0.0,
+            max_width: f32::MAX,
+            priority: 0,
+            color: Vec4::ONE,
+            occlusion: 0,
+            occluded_color: Vec4::ZERO,
+            falloff: 0,
+            falloff_power: 0.0,
+            placement: 0,
+            offset: Vec2::ZERO,
+            offset_blur: 0.0,
+            pattern: 0,
+            dashes: Vec3::ZERO,
+            noise: Vec3::ZERO,
+            pulse_width: Vec2::ZERO,
+            pulse_color: Vec4::ZERO,
+            pulse_period: 0.0,
+            corners: 0,
+            edges: OutlineEdges::default().flags(),
+            crease_angle: OutlineEdges::default().crease_angle,
+            gradient: Vec::new(),
+            fill_color: Vec4::ZERO,
+            fill_occlusion: 0,
+            fill_occluded_color: Vec4::ZERO,
+            contact_color: Vec4::ZERO,
+            contact_width: 0.0,
+            contact_epsilon: 0.0,
+            blend_mode: 0,
+            categories: OutlineCategories::default(),
+            layers: None,
+            group: None,
+            fade: Vec3::ZERO,
+            fade_curve: [1.0; FADE_CURVE_SAMPLES],
+            next_layer: None,
+            world_from_local: [Vec4::ZERO; 3],
+        }
+    }
+}
+
+impl ExtractedOutline {
+    /// This outline and the layers stacked over it, in drawing order.
+    pub fn iter_layers(&self) -> impl Iterator<Item = &ExtractedOutline> {
+        std::iter::successors(Some(self), |layer| layer.next_layer.as_deref())
+    }
+}
+
 // Ties the extracted `Target` to the source component's lifecycle: removing
 // `MeshOutline` removes its `ExtractedOutline` from the render world.
 impl SyncComponent for MeshOutline {
     type Target = ExtractedOutline;
 }
 
+impl SyncComponent for MeshOutlines {
+    type Target = ExtractedOutline;
+}
+
+impl SyncComponent for FadingOutline {
+    type Target = ExtractedOutline;
+}
+
+/// `pulse_color` of outlines whose pulse keeps their color; the negative alpha
+/// tells the compose shader to skip the tint.
+pub(crate) const NO_PULSE_COLOR: Vec4 = Vec4::new(0.0, 0.0, 0.0, -1.0);
+
+/// Sorts the stops of `gradient` and converts their colors to linear.
+fn extract_gradient(gradient: &OutlineGradient) -> Vec<(f32, Vec4)> {
+    let mut stops: Vec<_> = gradient
+        .stops
+        .iter()
+        .take(MAX_GRADIENT_STOPS)
+        .map(|&(offset, color)| {
+            let color: LinearRgba = color.into();
+            (offset.clamp(0.0, 1.0), color.to_vec4())
+        })
+        .collect();
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+    stops
+}
+
+/// Converts one outline layer to its render world representation.
+fn extract_outline(
+    outline: &MeshOutline,
+    transform: &GlobalTransform,
+    layers: Option<&OutlineLayers>,
+    group: Option<&OutlineGroup>,
+    fade: Option<&OutlineFade>,
+) -> ExtractedOutline {
+    let linear_color: LinearRgba = outline.color.into();
+    let occluded_color: LinearRgba = outline.occlusion.occluded_color().into();
+    let fill = outline.fill.as_ref();
+    let fill_color = LinearRgba::from(fill.map_or(Color::NONE, |fill| fill.color)).to_vec4();
+    let fill_occluded_color: LinearRgba = fill
+        .map_or(Color::NONE, |fill| fill.occlusion.occluded_color())
+        .into();
+    let contact_line = outline.contact_line.as_ref();
+    let (fade, fade_curve) = OutlineFade::gpu_params(fade);
+    ExtractedOutline {
+        intensity: outline.intensity,
+        width: outline.width,
+        min_width: outline.min_width.unwrap_or(0.0),
+        max_width: outline.max_width.unwrap_or(f32::MAX),
+        priority: outline.priority,
+        color: linear_color.to_vec4(),
+        occlusion: outline.occlusion.mode(),
+        occluded_color: occluded_color.to_vec4(),
+        falloff: outline.falloff.mode(),
+        falloff_power: outline.falloff.power(),
+        pattern: outline.pattern.mode(),
+        dashes: outline.pattern.dashes(),
+        noise: outline
+            .noise
+            .as_ref()
+            .map_or(Vec3::ZERO, OutlineNoise::params),
+        pulse_width: outline.pulse.map_or(Vec2::ZERO, |pulse| {
+            Vec2::new(pulse.min_width, pulse.max_width)
+        }),
+        pulse_color: outline
+            .pulse
+            .and_then(|pulse| pulse.color_b)
+            .map_or(NO_PULSE_COLOR, |color| LinearRgba::from(color).to_vec4()),
+        pulse_period: outline.pulse.map_or(0.0, |pulse| pulse.period.max(0.0)),
+        placement: outline.placement.mode(),
+        offset: outline
+            .offset
+            .map_or(Vec2::ZERO, |offset| offset.screen_offset),
+        offset_blur: outline.offset.map_or(0.0, |offset| offset.blur.max(0.0)),
+        corners: outline.corners.mode(),
+        edges: outline.edges.flags(),
+        crease_angle: outline.edges.crease_angle,
+        gradient: outline
+            .gradient
+            .as_ref()
+            .map(extract_gradient)
+            .unwrap_or_default(),
+        fill_color: fill_color
+            .truncate()
+            .extend(fill.map_or(0.0, |fill| fill.opacity)),
+        fill_occlusion: fill.map_or(0, |fill| fill.occlusion.mode()),
+        fill_occluded_color: fill_occluded_color.to_vec4(),
+        contact_color: LinearRgba::from(contact_line.map_or(Color::NONE, |line| line.color))
+            .to_vec4(),
+        contact_width: contact_line.map_or(0.0, |line| line.width),
+        contact_epsilon: contact_line.map_or(0.0, |line| line.epsilon),
+        blend_mode: outline.blend_mode.mode(),
+        categories: outline.categories,
+        layers: layers.map(|layers| layers.0.clone()),
+        group: group.and_then(OutlineGroup::id),
+        fade,
+        fade_curve,
+        next_layer: None,
+        world_from_local: Affine3::from(transform.affine()).to_transpose(),
+    }
+}
+
+/// Converts the layers of a stack to a chain headed by the first one.
+fn extract_stack(
+    outlines: &[MeshOutline],
+    transform: &GlobalTransform,
+    layers: Option<&OutlineLayers>,
+    group: Option<&OutlineGroup>,
+    fade: Option<&OutlineFade>,
+) -> Option<ExtractedOutline> {
+    // Link the layers from the last one up, so the first layer heads the
+    // chain.
+    outlines
+        .iter()
+        .take(MAX_OUTLINE_LAYERS)
+        .rev()
+        .fold(None, |next_layer, outline| {
+            Some(ExtractedOutline {
+                next_layer: next_layer.map(Box::new),
+                ..extract_outline(outline, transform, layers, group, fade)
+            })
+        })
+}
+
 impl ExtractComponent for MeshOutline {
-    type QueryData = (Entity, &'static MeshOutline, &'static GlobalTransform);
+    type QueryData = (
+        Entity,
+        &'static MeshOutline,
+        &'static GlobalTransform,
+        Option<&'static OutlineLayers>,
+        Option<&'static OutlineGroup>,
+        Option<&'static OutlineFade>,
+    );
+
+    // `MeshOutlines` takes precedence over `MeshOutline`.
+    type QueryFilter = (With<Mesh3d>, Without<MeshOutlines>);
+    type Out = ExtractedOutline;
+
+    fn extract_component(
+        (_entity, outline, transform, layers, group, fade): bevy::ecs::query::QueryItem<
+            '_,
+            '_,
+            Self::QueryData,
+        >,
+    ) -> Option<Self::Out> {
+        Some(extract_outline(outline, transform, layers, group, fade))
+    }
+}
+
+impl ExtractComponent for MeshOutlines {
+    type QueryData = (
+        Entity,
+        &'static MeshOutlines,
+        &'static GlobalTransform,
+        Option<&'static OutlineLayers>,
+        Option<&'static OutlineGroup>,
+        Option<&'static OutlineFade>,
+    );
 
     type QueryFilter = With<Mesh3d>;
     type Out = ExtractedOutline;
 
     fn extract_component(
-        (_entity, outline, transform): bevy::ecs::query::QueryItem<'_, '_, Self::QueryData>,
+        (_entity, outlines, transform, layers, group, fade): bevy::ecs::query::QueryItem<
+            '_,
+            '_,
+            Self::QueryData,
+        >,
     ) -> Option<Self::Out> {
-        let linear_color: LinearRgba = outline.color.into();
-        Some(ExtractedOutline {
-            intensity: outline.intensity,
-            width: outline.width,
-            priority: outline.priority,
-            color: linear_color.to_vec4(),
-            world_from_local: Affine3::from(transform.affine()).to_transpose(),
-        })
+        extract_stack(&outlines.0, transform, layers, group, fade)
+    }
+}
+
+impl ExtractComponent for FadingOutline {
+    type QueryData = (
+        Entity,
+        &'static FadingOutline,
+        &'static GlobalTransform,
+        Option<&'static OutlineLayers>,
+        Option<&'static OutlineGroup>,
+        Option<&'static OutlineFade>,
+    );
+
+    // An outline inserted again in the same frame replaces the fading one.
+    type QueryFilter = (With<Mesh3d>, Without<MeshOutline>, Without<MeshOutlines>);
+    type Out = ExtractedOutline;
+
+    fn extract_component(
+        (_entity, fading, transform, layers, group, fade): bevy::ecs::query::QueryItem<
+            '_,
+            '_,
+            Self::QueryData,
+        >,
+    ) -> Option<Self::Out> {
+        extract_stack(&fading.layers, transform, layers, group, fade)
     }
 }
 
 #[derive(Resource, Clone, Default)]
 pub struct ExtractedOutlines(MainEntityHashMap<ExtractedOutline>);
 
-/// Mirrors the outlines into a `MainEntity`-keyed resource for random access.
+/// Rank of each extracted outline's entity in [`Entity`] order, which settles
+/// ties between outlines the same way every frame.
+#[derive(Resource, Clone, Default)]
+pub struct OutlineTieBreaks(MainEntityHashMap<u32>);
+
+/// Mirrors the outlines into a `MainEntity`-keyed resource for random access,
+/// and ranks their entities into [`OutlineTieBreaks`].
 ///
 /// Reads the main world, not the render world's `ExtractedOutline`s: those are
 /// inserted by `ExtractComponentPlugin`'s commands at a later sync point, which
@@ -207,12 +1771,25 @@ pub struct ExtractedOutlines(MainEntityHashMap<ExtractedOutline>);
 /// appearances make `queue_outline`'s live keys miss.
 fn extract_outlines_to_resource(
     mut extracted_outlines: ResMut<ExtractedOutlines>,
+    mut tie_breaks: ResMut<OutlineTieBreaks>,
     outlines: Extract<
         Query<
             <MeshOutline as ExtractComponent>::QueryData,
             <MeshOutline as ExtractComponent>::QueryFilter,
         >,
     >,
+    stacked_outlines: Extract<
+        Query<
+            <MeshOutlines as ExtractComponent>::QueryData,
+            <MeshOutlines as ExtractComponent>::QueryFilter,
+        >,
+    >,
+    fading_outlines: Extract<
+        Query<
+            <FadingOutline as ExtractComponent>::QueryData,
+            <FadingOutline as ExtractComponent>::QueryFilter,
+        >,
+    >,
 ) {
     extracted_outlines.0.clear();
 
@@ -225,4 +1802,207 @@ fn extract_outlines_to_resource(
             .0
             .insert(MainEntity::from(entity), outline);
     }
+
+    for item in stacked_outlines.iter() {
+        let entity = item.0;
+        let Some(outline) = MeshOutlines::extract_component(item) else {
+            continue;
+        };
+        extracted_outlines
+            .0
+            .insert(MainEntity::from(entity), outline);
+    }
+
+    for item in fading_outlines.iter() {
+        let entity = item.0;
+        let Some(outline) = FadingOutline::extract_component(item) else {
+            continue;
+        };
+        extracted_outlines
+            .0
+            .insert(MainEntity::from(entity), outline);
+    }
+
+    let mut entities: Vec<MainEntity> = extracted_outlines.0.keys().copied().collect();
+    entities.sort_unstable_by_key(|entity| entity.id());
+    tie_breaks.0.clear();
+    tie_breaks.0.extend(
+        entities
+            .into_iter()
+            .enumerate()
+            .map(|(rank, entity)| (entity, rank as u32)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falloffs_fade_from_the_silhouette_to_the_outer_edge() {
+        let falloffs = [
+            OutlineFalloff::Linear,
+            OutlineFalloff::Smoothstep,
+            OutlineFalloff::Exponential { power: 4.0 },
+        ];
+        for falloff in falloffs {
+            assert_eq!(falloff.opacity(0.0), 1.0, "{falloff:?}");
+            assert!(falloff.opacity(1.0).abs() < 1e-6, "{falloff:?}");
+            assert!(falloff.opacity(0.25) > falloff.opacity(0.75), "{falloff:?}");
+        }
+        assert_eq!(OutlineFalloff::Solid.opacity(0.9), 1.0);
+
+        assert_eq!(OutlineFalloff::Linear.opacity(0.5), 0.5);
+        assert_eq!(OutlineFalloff::Smoothstep.opacity(0.5), 0.5);
+        assert!(OutlineFalloff::Smoothstep.opacity(0.1) > OutlineFalloff::Linear.opacity(0.1));
+        // A higher power pulls the outline in towards the silhouette.
+        let exponential = |power| OutlineFalloff::Exponential { power }.opacity(0.5);
+        assert!(exponential(8.0) < exponential(2.0));
+        assert!(exponential(2.0) < 0.5);
+    }
+
+    #[test]
+    fn placements_split_the_width_across_the_silhouette() {
+        let sides = |placement: OutlinePlacement| {
+            (
+                placement.band_width(6.0, true),
+                placement.band_width(6.0, false),
+            )
+        };
+        assert_eq!(sides(OutlinePlacement::Outer), (0.0, 6.0));
+        assert_eq!(sides(OutlinePlacement::Inner), (6.0, 0.0));
+        assert_eq!(sides(OutlinePlacement::Centered), (3.0, 3.0));
+    }
+
+    #[test]
+    fn overlap_candidates_are_clamped_to_the_limit() {
+        let settings = OutlineCameraSettings::default();
+        assert_eq!(
+            settings
+                .clone()
+                .with_overlap_candidates(MAX_OVERLAP_CANDIDATES + 1)
+                .overlap_candidates,
+            MAX_OVERLAP_CANDIDATES
+        );
+        assert_eq!(settings.with_overlap_candidates(0).overlap_candidates, 1);
+    }
+
+    #[test]
+    fn corners_measure_diagonals_differently() {
+        let diagonal = Vec2::new(3.0, -4.0);
+        assert_eq!(OutlineCorners::Round.distance(diagonal), 5.0);
+        assert_eq!(OutlineCorners::Square.distance(diagonal), 4.0);
+        assert_eq!(OutlineCorners::Diamond.distance(diagonal), 7.0);
+
+        // Straight along an axis, every shape measures the same.
+        for corners in [
+            OutlineCorners::Round,
+            OutlineCorners::Square,
+            OutlineCorners::Diamond,
+        ] {
+            assert_eq!(corners.distance(Vec2::new(0.0, -6.0)), 6.0, "{corners:?}");
+        }
+    }
+
+    /// Stops are clamped to the band, sorted and capped at
+    /// [`MAX_GRADIENT_STOPS`].
+    #[test]
+    fn gradient_stops_are_clamped_sorted_and_capped() {
+        let stops = extract_gradient(&OutlineGradient::new([
+            (0.8, Color::WHITE),
+            (-1.0, Color::BLACK),
+            (2.0, Color::linear_rgb(1.0, 0.0, 0.0)),
+        ]));
+        assert_eq!(
+            stops,
+            [
+                (0.0, Vec4::new(0.0, 0.0, 0.0, 1.0)),
+                (0.8, Vec4::ONE),
+                (1.0, Vec4::new(1.0, 0.0, 0.0, 1.0)),
+            ]
+        );
+
+        let many = OutlineGradient::new((0..12).map(|i| (i as f32 / 11.0, Color::WHITE)));
+        assert_eq!(extract_gradient(&many).len(), MAX_GRADIENT_STOPS);
+    }
+
+    #[test]
+    fn gradients_interpolate_between_stops_and_hold_past_the_ends() {
+        let gradient = OutlineGradient::new([
+            (0.75, Color::linear_rgb(0.0, 0.0, 1.0)),
+            (0.25, Color::linear_rgb(1.0, 0.0, 0.0)),
+        ]);
+        let red = LinearRgba::rgb(1.0, 0.0, 0.0);
+        let blue = LinearRgba::rgb(0.0, 0.0, 1.0);
+
+        assert_eq!(gradient.color_at(0.0), Some(red));
+        assert_eq!(gradient.color_at(0.25), Some(red));
+        assert_eq!(gradient.color_at(0.5), Some(LinearRgba::rgb(0.5, 0.0, 0.5)));
+        assert_eq!(gradient.color_at(0.75), Some(blue));
+        assert_eq!(gradient.color_at(1.0), Some(blue));
+        assert_eq!(OutlineGradient::default().color_at(0.5), None);
+    }
+
+    #[test]
+    fn patterns_alternate_dashes_and_gaps_along_the_outline() {
+        let dashed = OutlinePattern::Dashed {
+            dash: 4.0,
+            gap: 2.0,
+            speed: 3.0,
+        };
+        assert!(dashed.draws(1.0, 2.0, 0.0));
+        assert!(!dashed.draws(5.0, 2.0, 0.0));
+        // A second later the dashes have moved 3 pixels along.
+        assert!(dashed.draws(5.0, 2.0, 1.0));
+        assert!(!dashed.draws(2.0, 2.0, 1.0));
+
+        // Dots are as long as the band is wide, whatever the time.
+        let dots = |position| OutlinePattern::Dotted.draws(position, 3.0, 7.0);
+        assert!(dots(1.0) && !dots(4.0) && dots(7.0));
+
+        assert!(OutlinePattern::Solid.draws(5.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn pulses_swing_between_their_widths_each_period() {
+        let pulse = OutlinePulse::new(2.0, 6.0, 2.0);
+        assert_eq!(pulse.width_at(0.0), 2.0);
+        assert_eq!(pulse.width_at(1.0), 6.0);
+        assert!((pulse.width_at(0.5) - 4.0).abs() < 1e-5);
+        assert!((pulse.width_at(2.0) - 2.0).abs() < 1e-5);
+        assert!(pulse.phase(0.25) < pulse.phase(0.75));
+    }
+
+    #[test]
+    fn interior_edges_follow_creases_and_depth_jumps() {
+        let at = |normal: Vec3, depth| normal.extend(depth);
+        let flat = at(Vec3::Z, 10.0);
+        let tilted = at(Quat::from_rotation_x(0.8).mul_vec3(Vec3::Z), 10.0);
+        let behind = at(Vec3::Z, 11.0);
+
+        let creases = OutlineEdges::default().with_creases(true);
+        assert!(creases.separates(flat, tilted));
+        assert!(!creases.separates(flat, at(Vec3::ZERO, 10.0)));
+        assert!(!creases.with_crease_angle(1.0).separates(flat, tilted));
+        assert!(!creases.separates(flat, behind));
+
+        // Only the near side of a depth jump is an edge.
+        let depth = OutlineEdges::default().with_depth_discontinuity(true);
+        assert!(depth.separates(flat, behind));
+        assert!(!depth.separates(behind, flat));
+        assert!(!depth.separates(flat, at(Vec3::Z, 10.1)));
+
+        assert!(!OutlineEdges::default().separates(flat, tilted));
+    }
+
+    #[test]
+    fn group_ids_past_the_largest_are_ignored() {
+        assert_eq!(OutlineGroup(0).id(), Some(0));
+        assert_eq!(
+            OutlineGroup(MAX_OUTLINE_GROUP).id(),
+            Some(MAX_OUTLINE_GROUP)
+        );
+        assert_eq!(OutlineGroup(MAX_OUTLINE_GROUP + 1).id(), None);
+        assert_eq!(OutlineGroup(u32::MAX).id(), None);
+    }
 }
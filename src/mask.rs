@@ -11,10 +11,11 @@ use bevy_render::{
     sync_world::MainEntity,
 };
 
-use crate::ExtractedOutline;
+use crate::{ExtractedOutline, FADE_CURVE_SAMPLES};
 
 /// Hashable/orderable representation of an outline's appearance (everything in
-/// [`crate::uniforms::OutlineUniform`] except the per-instance transform).
+/// [`crate::uniforms::OutlineUniform`] and [`crate::uniforms::OutlineStyleUniform`]
+/// except the per-instance transform and tie-break rank).
 ///
 /// This is part of the [`OutlineBatchSetKey`], mirroring how Bevy's own opaque
 /// phase keys its batch sets on the material bind group. A batch set draws with
@@ -22,22 +23,91 @@ use crate::ExtractedOutline;
 /// instance in it must share the same appearance — floats are stored as their
 /// bit patterns so the key can derive `Eq`/`Ord`/`Hash`, and identical `f32`
 /// values always share bit patterns, so this never merges visually different
-/// outlines.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// outlines. Stacked layers are part of the key through `next_layer`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct OutlineKey {
     pub intensity: u32,
     pub width: u32,
-    pub priority: u32,
+    pub width_unit: u32,
+    pub min_width: u32,
+    pub max_width: u32,
+    pub priority: i32,
     pub color: [u32; 4],
+    pub occlusion: u32,
+    pub occluded_color: [u32; 4],
+    pub falloff: u32,
+    pub falloff_power: u32,
+    pub pattern: u32,
+    pub dashes: [u32; 3],
+    pub noise: [u32; 3],
+    pub pulse_width: [u32; 2],
+    pub pulse_color: [u32; 4],
+    pub pulse_period: u32,
+    pub placement: u32,
+    pub offset: [u32; 2],
+    pub offset_blur: u32,
+    pub corners: u32,
+    pub edges: u32,
+    pub crease_angle: u32,
+    pub gradient: Vec<(u32, [u32; 4])>,
+    pub fill_color: [u32; 4],
+    pub fill_occlusion: u32,
+    pub fill_occluded_color: [u32; 4],
+    pub contact_color: [u32; 4],
+    pub contact_width: u32,
+    pub contact_epsilon: u32,
+    pub blend_mode: u32,
+    pub group: Option<u32>,
+    pub fade: [u32; 3],
+    pub fade_curve: [u32; FADE_CURVE_SAMPLES],
+    pub next_layer: Option<Box<OutlineKey>>,
 }
 
 impl OutlineKey {
     pub fn from_outline(outline: &ExtractedOutline) -> Self {
         Self {
             intensity: outline.intensity.to_bits(),
-            width: outline.width.to_bits(),
-            priority: outline.priority.to_bits(),
+            width: outline.width.value().to_bits(),
+            width_unit: outline.width.unit(),
+            min_width: outline.min_width.to_bits(),
+            max_width: outline.max_width.to_bits(),
+            priority: outline.priority,
             color: outline.color.to_array().map(f32::to_bits),
+            occlusion: outline.occlusion,
+            occluded_color: outline.occluded_color.to_array().map(f32::to_bits),
+            falloff: outline.falloff,
+            falloff_power: outline.falloff_power.to_bits(),
+            pattern: outline.pattern,
+            dashes: outline.dashes.to_array().map(f32::to_bits),
+            noise: outline.noise.to_array().map(f32::to_bits),
+            pulse_width: outline.pulse_width.to_array().map(f32::to_bits),
+            pulse_color: outline.pulse_color.to_array().map(f32::to_bits),
+            pulse_period: outline.pulse_period.to_bits(),
+            placement: outline.placement,
+            offset: outline.offset.to_array().map(f32::to_bits),
+            offset_blur: outline.offset_blur.to_bits(),
+            corners: outline.corners,
+            edges: outline.edges,
+            crease_angle: outline.crease_angle.to_bits(),
+            gradient: outline
+                .gradient
+                .iter()
+                .map(|(offset, color)| (offset.to_bits(), color.to_array().map(f32::to_bits)))
+                .collect(),
+            fill_color: outline.fill_color.to_array().map(f32::to_bits),
+            fill_occlusion: outline.fill_occlusion,
+            fill_occluded_color: outline.fill_occluded_color.to_array().map(f32::to_bits),
+            contact_color: outline.contact_color.to_array().map(f32::to_bits),
+            contact_width: outline.contact_width.to_bits(),
+            contact_epsilon: outline.contact_epsilon.to_bits(),
+            blend_mode: outline.blend_mode,
+            group: outline.group,
+            fade: outline.fade.to_array().map(f32::to_bits),
+            fade_curve: outline.fade_curve.map(f32::to_bits),
+            next_layer: outline
+                .next_layer
+                .as_deref()
+                .map(|layer| Box::new(Self::from_outline(layer))),
         }
     }
 }
@@ -51,6 +121,9 @@ pub(crate) struct OutlineBatchSetKey {
     /// multi-drawn batch set never spans instances that would need different
     /// outline bind groups.
     pub outline: OutlineKey,
+    /// The entity's rank in [`crate::OutlineTieBreaks`], also part of its
+    /// outline bind group, so outlined entities draw their masks one by one.
+    pub tie_break: u32,
 }
 
 impl PhaseItemBatchSetKey for OutlineBatchSetKey {
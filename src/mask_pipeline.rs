@@ -27,7 +27,10 @@ use nonmax::NonMaxU32;
 
 use crate::shaders::MASK_SHADER_HANDLE;
 
-use super::{ExtractedOutline, ExtractedOutlines, uniforms::OutlineUniform};
+use super::{
+    ExtractedOutline, ExtractedOutlines,
+    uniforms::{OutlineUniform, OutlineViewUniform},
+};
 
 #[derive(Resource)]
 pub struct MeshMaskPipeline {
@@ -45,7 +48,10 @@ pub fn init_mesh_mask_pipeline(mut commands: Commands, mesh_pipeline: Res<MeshPi
         "OutlineInstance",
         &BindGroupLayoutEntries::sequential(
             ShaderStages::VERTEX_FRAGMENT,
-            (uniform_buffer::<OutlineUniform>(false),),
+            (
+                uniform_buffer::<OutlineUniform>(false),
+                uniform_buffer::<OutlineViewUniform>(true),
+            ),
         ),
     );
 
@@ -78,7 +84,13 @@ impl SpecializedMeshPipeline for MeshMaskPipeline {
                     blend: None,
                     write_mask: ColorWrites::ALL,
                 }),
-                // RT1: appearance data (color.rgb, priority)
+                // RT1: appearance data (style index, priority, silhouette id, world scale)
+                Some(ColorTargetState {
+                    format: TextureFormat::Rgba32Float,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                }),
+                // RT2: edge data (world normal, view depth)
                 Some(ColorTargetState {
                     format: TextureFormat::Rgba32Float,
                     blend: None,
@@ -14,11 +14,15 @@ use crate::MeshOutline3d;
 
 use super::{
     compose::ComposeOutputPipeline,
-    flood::{FloodSettings, JumpFloodPass},
+    flood::{FloodInputs, FloodSettings, JumpFloodPass},
+    render::OutlineStyles,
+    scene_edges::draw_scene_edges,
     texture::FloodTextures,
+    view::{ExtractedOutlineView, OutlineViewUniformOffset, OutlineViewUniforms},
 };
 
 /// Renders the mesh outline effect. Runs as a system in the `Core3d` schedule.
+#[allow(clippy::type_complexity)]
 pub fn mesh_outline_pass(
     world: &World,
     view: ViewQuery<(
@@ -28,6 +32,8 @@ pub fn mesh_outline_pass(
         &FloodTextures,
         &ViewPrepassTextures,
         &FloodSettings,
+        &ExtractedOutlineView,
+        &OutlineViewUniformOffset,
         &Msaa,
     )>,
     mut ctx: RenderContext,
@@ -40,9 +46,15 @@ pub fn mesh_outline_pass(
         flood_textures,
         prepass_textures,
         flood_settings,
+        outline_view,
+        view_uniform_offset,
         msaa,
     ) = view.into_inner();
 
+    if !outline_view.enabled {
+        return;
+    }
+
     let render_context = &mut ctx;
 
     let Some(outline_phases) = world.get_resource::<ViewBinnedRenderPhases<MeshOutline3d>>() else {
@@ -95,6 +107,21 @@ pub fn mesh_outline_pass(
         depth_slice: None,
     };
 
+    let edge_color_attachment = RenderPassColorAttachment {
+        view: &flood_textures.edge_texture.default_view,
+        resolve_target: None,
+        ops: Operations {
+            load: LoadOp::Clear(wgpu_types::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            }),
+            store: StoreOp::Store,
+        },
+        depth_slice: None,
+    };
+
     let outline_depth_view = flood_textures
         .outline_depth_texture
         .create_view(&TextureViewDescriptor::default());
@@ -105,6 +132,7 @@ pub fn mesh_outline_pass(
             color_attachments: &[
                 Some(flood_color_attachment),
                 Some(appearance_color_attachment),
+                Some(edge_color_attachment),
             ],
             depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
                 view: &outline_depth_view,
@@ -120,7 +148,7 @@ pub fn mesh_outline_pass(
         });
 
         if let Some(viewport) = camera.viewport.as_ref() {
-            init_pass.set_camera_viewport(viewport);
+            init_pass.set_camera_viewport(&outline_view.texture_viewport(viewport));
         }
 
         if let Err(err) = outline_phase.render(&mut init_pass, world, view_entity) {
@@ -157,10 +185,19 @@ pub fn mesh_outline_pass(
         return;
     };
 
+    let Some(outline_styles) = world.resource::<OutlineStyles>().binding() else {
+        return;
+    };
+    let Some(view_uniforms) = world.resource::<OutlineViewUniforms>().uniforms.binding() else {
+        return;
+    };
+
     let post_process = view_target.post_process_write();
 
     // Flooding!
 
+    // Enough passes for seeds to reach every pixel of their band, including
+    // bands moved away from the silhouette by an `OutlineOffset`.
     let outline_width: f32 = flood_settings.width;
 
     let passes = if outline_width > 0.0 {
@@ -172,16 +209,38 @@ pub fn mesh_outline_pass(
         0
     };
 
+    // Cloned so the inputs don't borrow `flood_textures`, which is flipped
+    // between passes.
+    let flood_data_view = flood_textures.outline_flood_data.default_view.clone();
+    let appearance_view = flood_textures.appearance_texture.default_view.clone();
+    let edge_view = flood_textures.edge_texture.default_view.clone();
+    let flood_inputs = FloodInputs {
+        depth_texture: &outline_depth_view,
+        color_texture: &flood_data_view,
+        appearance_texture: &appearance_view,
+        edge_texture: &edge_view,
+        styles: outline_styles.clone(),
+        view_uniforms: view_uniforms.clone(),
+        view_offset: view_uniform_offset.offset,
+    };
+
+    flood_textures.flip();
+    jump_flood_pass.execute_seed(render_context, &flood_textures, &flood_inputs);
+
     for size in (0..passes).rev() {
         flood_textures.flip();
-        jump_flood_pass.execute(
+        jump_flood_pass.execute(render_context, &flood_textures, &flood_inputs, size);
+    }
+
+    if let Some(scene_edge_texture) = flood_textures.scene_edge_texture.as_ref() {
+        draw_scene_edges(
+            world,
             render_context,
-            flood_textures.input(),
-            flood_textures.output(),
-            &outline_depth_view,
-            &flood_textures.outline_flood_data.default_view,
-            &flood_textures.appearance_texture.default_view,
-            size,
+            scene_edge_texture,
+            prepass_textures,
+            multisampled,
+            view_uniforms.clone(),
+            view_uniform_offset.offset,
         );
     }
 
@@ -195,14 +254,36 @@ pub fn mesh_outline_pass(
             post_process.source,
             // binding 1: texture_sampler - Use the sampler created for the pipeline
             &jump_flood_pass.pipeline.sampler,
-            // binding 2: flood_texture - The flood output texture
-            &flood_textures.output.default_view,
+            // binding 2: flood_texture - The output of the final flood pass.
+            // `output()` follows the ping-pong flips; the `output` field is
+            // only the final texture after an even number of passes.
+            &flood_textures.output().default_view,
             // binding 3: appearance_texture - The appearance data texture
             &flood_textures.appearance_texture.default_view,
             // binding 4: depth_texture - Global depth texture
             &global_depth.texture.default_view,
             // binding 5: outline_depth_texture - Use the outline depth texture
             &outline_depth_view,
+            // binding 6: styles - Per-appearance outline styles
+            outline_styles,
+            // binding 7: view - Per-view outline settings
+            view_uniforms,
+            // binding 8: secondary_flood_texture - Runner-up seeds, unused
+            // unless the view keeps two overlapping outlines
+            &flood_textures
+                .secondary_output()
+                .unwrap_or(flood_textures.output())
+                .default_view,
+            // binding 9: edge_texture - Normals and depths of the outlined
+            // meshes
+            &flood_textures.edge_texture.default_view,
+            // binding 10: scene_edge_texture - Coverage of the scene edges,
+            // unused unless the view draws them
+            &flood_textures
+                .scene_edge_texture
+                .as_ref()
+                .unwrap_or(&flood_textures.edge_texture)
+                .default_view,
         )),
     );
 
@@ -221,7 +302,7 @@ pub fn mesh_outline_pass(
         });
 
         render_pass.set_render_pipeline(pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_bind_group(0, &bind_group, &[view_uniform_offset.offset]);
         render_pass.draw(0..3, 0..1);
     }
 }
@@ -0,0 +1,115 @@
+use core::any::TypeId;
+
+use bevy::{camera::visibility::VisibilityClass, light::NotShadowCaster, prelude::*};
+
+use super::OutlineOnly;
+
+/// Marks a [`NotShadowCaster`] that was inserted for [`OutlineOnly`], so it is
+/// only removed again if the user didn't add it themselves.
+#[derive(Component)]
+pub(crate) struct OutlineOnlyShadow;
+
+/// Moves [`OutlineOnly`] meshes out of the `Mesh3d` visibility class, which the
+/// main and prepass phases queue from, into their own class, which only
+/// `queue_outline` reads. Visibility checks still frustum cull them and set
+/// their `ViewVisibility`, so the meshes are still extracted.
+///
+/// Shadow passes don't go by class, so the meshes also get [`NotShadowCaster`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn hide_outline_only_meshes(
+    mut commands: Commands,
+    mut meshes: Query<
+        (
+            Entity,
+            &mut VisibilityClass,
+            Ref<OutlineOnly>,
+            Has<NotShadowCaster>,
+        ),
+        Or<(Added<OutlineOnly>, Changed<VisibilityClass>)>,
+    >,
+) {
+    let mesh_class = TypeId::of::<Mesh3d>();
+    let outline_only_class = TypeId::of::<OutlineOnly>();
+
+    for (entity, mut class, outline_only, casts_no_shadow) in meshes.iter_mut() {
+        // Only touch the class when needed, so it isn't flagged as changed
+        // again every frame.
+        if class.contains(&mesh_class) || !class.contains(&outline_only_class) {
+            class.retain(|id| *id != mesh_class && *id != outline_only_class);
+            class.push(outline_only_class);
+        }
+        if outline_only.is_added() && !casts_no_shadow {
+            commands
+                .entity(entity)
+                .insert((NotShadowCaster, OutlineOnlyShadow));
+        }
+    }
+}
+
+/// Draws the mesh again when [`OutlineOnly`] is removed.
+pub(crate) fn show_outline_only_mesh(
+    remove: On<Remove, OutlineOnly>,
+    mut commands: Commands,
+    mut meshes: Query<(&mut VisibilityClass, Has<Mesh3d>, Has<OutlineOnlyShadow>)>,
+) {
+    let entity = remove.entity;
+    let Ok((mut class, is_mesh, added_shadow)) = meshes.get_mut(entity) else {
+        return;
+    };
+
+    let outline_only_class = TypeId::of::<OutlineOnly>();
+    class.retain(|id| *id != outline_only_class);
+    if is_mesh && !class.contains(&TypeId::of::<Mesh3d>()) {
+        class.push(TypeId::of::<Mesh3d>());
+    }
+    if added_shadow {
+        // The entity may be despawning.
+        commands
+            .entity(entity)
+            .try_remove::<(NotShadowCaster, OutlineOnlyShadow)>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(app: &App, entity: Entity) -> Vec<TypeId> {
+        app.world().get::<VisibilityClass>(entity).unwrap().to_vec()
+    }
+
+    /// Outline-only meshes leave the mesh class and cast no shadows until
+    /// `OutlineOnly` is removed, without touching a user's `NotShadowCaster`.
+    #[test]
+    fn outline_only_meshes_are_hidden_until_removed() {
+        let mut app = App::new();
+        app.add_systems(Update, hide_outline_only_meshes)
+            .add_observer(show_outline_only_mesh);
+
+        // Without the render plugins, meshes don't require a class.
+        let mesh = || {
+            (
+                Mesh3d::default(),
+                VisibilityClass([TypeId::of::<Mesh3d>()].into()),
+            )
+        };
+        let ghost = app.world_mut().spawn((mesh(), OutlineOnly)).id();
+        let unlit = app
+            .world_mut()
+            .spawn((mesh(), OutlineOnly, NotShadowCaster))
+            .id();
+        app.update();
+
+        assert_eq!(classes(&app, ghost), [TypeId::of::<OutlineOnly>()]);
+        assert!(app.world().entity(ghost).contains::<NotShadowCaster>());
+
+        for entity in [ghost, unlit] {
+            app.world_mut().entity_mut(entity).remove::<OutlineOnly>();
+        }
+        app.update();
+
+        assert_eq!(classes(&app, ghost), [TypeId::of::<Mesh3d>()]);
+        assert!(!app.world().entity(ghost).contains::<NotShadowCaster>());
+        assert!(app.world().entity(unlit).contains::<NotShadowCaster>());
+    }
+}
@@ -14,13 +14,18 @@ use bevy_render::{
 use crate::{
     DrawOutline,
     mask::{OutlineBatchSetKey, OutlineBinKey, OutlineKey},
+    view::ExtractedOutlineView,
 };
 
-use super::{ExtractedOutline, MeshOutline3d, OutlineCamera, mask_pipeline::MeshMaskPipeline};
+use super::{
+    ExtractedOutline, MeshOutline3d, OutlineCamera, OutlineLayers, OutlineOnly, OutlineTieBreaks,
+    mask_pipeline::MeshMaskPipeline,
+};
 
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn queue_outline(
     outlined_meshes: Query<&ExtractedOutline>,
+    tie_breaks: Res<OutlineTieBreaks>,
     draw_functions: Res<DrawFunctions<MeshOutline3d>>,
     mut outline_phases: ResMut<ViewBinnedRenderPhases<MeshOutline3d>>,
     mesh_outline_pipeline: Res<MeshMaskPipeline>,
@@ -34,11 +39,22 @@ pub fn queue_outline(
     // Governs the batch tier (multi-drawable / batchable / unbatchable) so the
     // outline phase matches how the main 3D phases process the same meshes.
     gpu_preprocessing_support: Res<GpuPreprocessingSupport>,
-    views: Query<(&ExtractedView, &RenderVisibleEntities), With<OutlineCamera>>,
+    views: Query<
+        (
+            &ExtractedView,
+            &ExtractedOutlineView,
+            &RenderVisibleEntities,
+        ),
+        With<OutlineCamera>,
+    >,
 ) {
     let draw_function = draw_functions.read().id::<DrawOutline>();
 
-    for (view, visible_entities) in views.iter() {
+    for (view, outline_view, visible_entities) in views.iter() {
+        if !outline_view.enabled {
+            continue;
+        }
+
         // The phase was reset to empty for this frame in `update_views`; here we
         // rebuild it from the currently visible, currently outlined meshes.
         let Some(outline_phase) = outline_phases.get_mut(&view.retained_view_entity) else {
@@ -50,15 +66,30 @@ pub fn queue_outline(
         };
 
         // `RenderVisibleEntities::get` now returns an optional class; iterate all
-        // visible mesh entities and keep only the outlined ones.
-        let Some(visible_meshes) = visible_entities.get::<Mesh3d>() else {
-            continue;
-        };
+        // visible mesh entities and keep only the outlined ones. `OutlineOnly`
+        // meshes are visible under their own class.
+        let visible_meshes = visible_entities
+            .get::<Mesh3d>()
+            .into_iter()
+            .chain(visible_entities.get::<OutlineOnly>())
+            .flat_map(|class| class.iter_visible())
+            .map(|entities| (entities, false));
+        // Meshes with `OutlineLayers` are listed under that class for the
+        // cameras that draw their outline, whether or not they see the mesh,
+        // and are only queued from there.
+        let layer_meshes = visible_entities
+            .get::<OutlineLayers>()
+            .into_iter()
+            .flat_map(|class| class.iter_visible())
+            .map(|entities| (entities, true));
 
-        for (&render_entity, &main_entity) in visible_meshes.iter_visible() {
+        for ((&render_entity, &main_entity), from_layers) in visible_meshes.chain(layer_meshes) {
             let Ok(outline) = outlined_meshes.get(render_entity) else {
                 continue;
             };
+            if outline.layers.is_some() != from_layers || !outline_view.shows(outline) {
+                continue;
+            }
             let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(main_entity)
             else {
                 tracing::warn!(target: "bevy_mesh_outline", "No mesh instance found for entity {:?}", main_entity);
@@ -102,15 +133,16 @@ pub fn queue_outline(
                     draw_function,
                     slabs: mesh_slabs,
                     outline: OutlineKey::from_outline(outline),
+                    tie_break: tie_breaks.0.get(&main_entity).copied().unwrap_or_default(),
                 },
                 OutlineBinKey {
                     asset_id: mesh_instance.mesh_asset_id().untyped(),
                 },
                 (render_entity, main_entity),
                 mesh_instance.current_uniform_index,
-                // Mirror the main 3D phases: instances of the same mesh with the
-                // same outline appearance batch (or multi-draw) into one draw,
-                // all sharing the representative entity's outline bind group.
+                // Mirror the main 3D phases, though each batch set holds a
+                // single entity, whose tie-break rank its outline bind group
+                // carries.
                 BinnedRenderPhaseType::mesh(
                     mesh_instance.should_batch(),
                     &gpu_preprocessing_support,
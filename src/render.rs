@@ -1,43 +1,55 @@
 use bevy::{
-    ecs::system::{SystemParamItem, lifetimeless::SRes},
+    ecs::system::{
+        SystemParamItem,
+        lifetimeless::{Read, SRes},
+    },
     platform::collections::{HashMap, HashSet},
     prelude::*,
 };
 use bevy_render::{
     render_phase::{RenderCommand, RenderCommandResult, TrackedRenderPass},
-    render_resource::{BindGroup, BindGroupEntry, BufferInitDescriptor, PipelineCache},
-    renderer::RenderDevice,
+    render_resource::{
+        BindGroup, BindGroupEntries, BindingResource, BufferId, BufferInitDescriptor,
+        PipelineCache, StorageBuffer,
+    },
+    renderer::{RenderDevice, RenderQueue},
 };
 use wgpu_types::BufferUsages;
 
 use super::{
-    ExtractedOutlines,
+    ExtractedOutline, ExtractedOutlines, OutlineTieBreaks,
     mask::{MeshOutline3d, OutlineKey},
     mask_pipeline::MeshMaskPipeline,
-    uniforms::OutlineUniform,
+    uniforms::{NO_LAYER, OutlineStyleUniform, OutlineUniform},
+    view::{OutlineViewUniformOffset, OutlineViewUniforms},
 };
 
 pub(crate) struct SetOutlineBindGroup<const I: usize>();
 
 impl<const I: usize> RenderCommand<MeshOutline3d> for SetOutlineBindGroup<I> {
     type Param = SRes<OutlineBindGroups>;
-    type ViewQuery = ();
+    type ViewQuery = Read<OutlineViewUniformOffset>;
     type ItemQuery = ();
 
     fn render<'w>(
         item: &MeshOutline3d,
-        _view: (),
+        view_uniform_offset: &'w OutlineViewUniformOffset,
         _entity_data: Option<()>,
         outline_bind_groups: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         let outline_bind_groups = outline_bind_groups.into_inner();
 
-        // Every instance in this batch shares the same appearance (it's part of
-        // the batch-set key), so a single bind group keyed by that appearance
-        // serves the whole batch.
-        if let Some(bind_group) = outline_bind_groups.0.get(&item.batch_set_key.outline) {
-            pass.set_bind_group(I, bind_group, &[]);
+        // Every instance in this batch shares the same appearance and rank
+        // (they're part of the batch-set key), so a single bind group keyed by
+        // them serves the whole batch.
+        let key = &item.batch_set_key;
+        if let Some(bind_group) = outline_bind_groups
+            .0
+            .get(&key.outline)
+            .and_then(|bind_groups| bind_groups.get(&key.tie_break))
+        {
+            pass.set_bind_group(I, bind_group, &[view_uniform_offset.offset]);
             RenderCommandResult::Success
         } else {
             // Bind group not ready yet, skip this frame
@@ -46,36 +58,144 @@ impl<const I: usize> RenderCommand<MeshOutline3d> for SetOutlineBindGroup<I> {
     }
 }
 
-/// One outline uniform bind group per distinct appearance, cached across frames.
+/// Outline uniform bind groups by appearance and tie-break rank, cached across
+/// frames.
 ///
-/// Because meshes only batch when they share an appearance (see
-/// [`crate::mask::OutlineBatchSetKey`]), keying the bind groups by appearance
-/// rather than by entity means we allocate O(distinct appearances) GPU resources
-/// per frame instead of one buffer + bind group per outlined entity every frame
-/// — and in the common case of a stable set of appearances, zero per frame.
+/// Each outlined entity has a rank of its own, so this holds one small uniform
+/// buffer per entity. Keying them by appearance and rank rather than by entity
+/// means they are only rebuilt when an entity's appearance or rank changes —
+/// and in the common case of a stable set of outlines, never.
 #[derive(Resource, Default)]
-pub struct OutlineBindGroups(HashMap<OutlineKey, BindGroup>);
+pub struct OutlineBindGroups(HashMap<OutlineKey, HashMap<u32, BindGroup>>);
 
+/// Storage buffer of [`OutlineStyleUniform`]s, one slot per distinct
+/// appearance, read by the compose pass.
+///
+/// The mask pass writes each pixel's slot into the appearance texture. A slot
+/// stays assigned to its appearance for as long as that appearance is live, so
+/// the slot baked into a cached [`OutlineBindGroups`] uniform never goes stale
+/// and the buffer is only re-uploaded when an appearance comes or goes.
+#[derive(Resource)]
+pub struct OutlineStyles {
+    slots: HashMap<OutlineKey, u32>,
+    free_slots: Vec<u32>,
+    buffer: StorageBuffer<Vec<OutlineStyleUniform>>,
+    dirty: bool,
+}
+
+impl Default for OutlineStyles {
+    fn default() -> Self {
+        // Zero-sized buffers can't be bound, so start with one (free) slot.
+        let mut buffer = StorageBuffer::from(vec![OutlineStyleUniform::default()]);
+        buffer.set_label(Some("outline_style_buffer"));
+
+        Self {
+            slots: HashMap::default(),
+            free_slots: vec![0],
+            buffer,
+            dirty: true,
+        }
+    }
+}
+
+impl OutlineStyles {
+    /// Returns the slot of `key`, assigning one if the appearance is new. The
+    /// layers stacked over it get slots of their own, linked from this one.
+    fn slot(&mut self, key: &OutlineKey, outline: &ExtractedOutline) -> u32 {
+        if let Some(&slot) = self.slots.get(key) {
+            return slot;
+        }
+
+        let next_layer = match (&key.next_layer, &outline.next_layer) {
+            (Some(next_key), Some(next_outline)) => self.slot(next_key, next_outline),
+            _ => NO_LAYER,
+        };
+        let style = OutlineStyleUniform::new(outline, next_layer);
+        let styles = self.buffer.get_mut();
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                styles[slot as usize] = style;
+                slot
+            }
+            None => {
+                styles.push(style);
+                styles.len() as u32 - 1
+            }
+        };
+
+        self.slots.insert(key.clone(), slot);
+        self.dirty = true;
+        slot
+    }
+
+    /// Frees the slots of appearances that are no longer live.
+    fn retain(&mut self, live_keys: &HashSet<OutlineKey>) {
+        let free_slots = &mut self.free_slots;
+        self.slots.retain(|key, slot| {
+            let live = live_keys.contains(key);
+            if !live {
+                free_slots.push(*slot);
+            }
+            live
+        });
+    }
+
+    pub fn binding(&self) -> Option<BindingResource<'_>> {
+        self.buffer.binding()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_outline_bind_groups(
     render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
     pipeline_cache: Res<PipelineCache>,
     outline_pipeline: Res<MeshMaskPipeline>,
     extracted_outlines: Res<ExtractedOutlines>,
+    tie_breaks: Res<OutlineTieBreaks>,
     mut outline_bind_groups: ResMut<OutlineBindGroups>,
-    mut live_keys: Local<HashSet<OutlineKey>>,
+    mut outline_styles: ResMut<OutlineStyles>,
+    view_uniforms: Res<OutlineViewUniforms>,
+    mut view_buffer_id: Local<Option<BufferId>>,
+    mut live_keys: Local<HashMap<OutlineKey, HashSet<u32>>>,
+    mut live_styles: Local<HashSet<OutlineKey>>,
 ) {
     live_keys.clear();
+    live_styles.clear();
+
+    // The bind groups also hold the view uniforms, so they are rebuilt when
+    // that buffer is reallocated.
+    let (Some(view_buffer), Some(view_uniforms)) = (
+        view_uniforms.uniforms.buffer(),
+        view_uniforms.uniforms.binding(),
+    ) else {
+        return;
+    };
+    if *view_buffer_id != Some(view_buffer.id()) {
+        outline_bind_groups.0.clear();
+        *view_buffer_id = Some(view_buffer.id());
+    }
 
-    for outline in extracted_outlines.0.values() {
+    for (main_entity, outline) in extracted_outlines.0.iter() {
         let key = OutlineKey::from_outline(outline);
-        if !live_keys.insert(key) {
-            // Already built (or reused) a bind group for this appearance.
-            continue;
+        let style_index = outline_styles.slot(&key, outline);
+        let tie_break = tie_breaks.0.get(main_entity).copied().unwrap_or_default();
+
+        let live_ranks = live_keys.entry(key.clone()).or_default();
+        if live_ranks.is_empty() {
+            // Stacked layers hold style slots too.
+            let mut layer = Some(&key);
+            while let Some(layer_key) = layer {
+                live_styles.insert(layer_key.clone());
+                layer = layer_key.next_layer.as_deref();
+            }
         }
+        live_ranks.insert(tie_break);
 
-        // Only touches the GPU for appearances we haven't cached yet.
-        outline_bind_groups.0.entry(key).or_insert_with(|| {
-            let outline_uniform = OutlineUniform::from(outline);
+        // Only touches the GPU for appearances and ranks we haven't cached yet.
+        let bind_groups = outline_bind_groups.0.entry(key).or_default();
+        bind_groups.entry(tie_break).or_insert_with(|| {
+            let outline_uniform = OutlineUniform::new(outline, style_index, tie_break);
 
             let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
                 label: Some("outline_uniform_buffer"),
@@ -86,19 +206,28 @@ pub fn prepare_outline_bind_groups(
             render_device.create_bind_group(
                 Some("outline_bind_group"),
                 &pipeline_cache.get_bind_group_layout(&outline_pipeline.outline_bind_group_layout),
-                &[BindGroupEntry {
-                    binding: 0,
-                    resource: buffer.as_entire_binding(),
-                }],
+                &BindGroupEntries::sequential((buffer.as_entire_binding(), view_uniforms.clone())),
             )
         });
     }
 
-    // Drop bind groups for appearances no longer in use so the cache stays
-    // bounded by the appearances actually on screen.
-    outline_bind_groups
-        .0
-        .retain(|key, _| live_keys.contains(key));
+    // Drop bind groups for appearances and ranks no longer in use so the
+    // cache stays bounded by the outlines actually on screen.
+    outline_bind_groups.0.retain(|key, bind_groups| {
+        let Some(live_ranks) = live_keys.get(key) else {
+            return false;
+        };
+        bind_groups.retain(|tie_break, _| live_ranks.contains(tie_break));
+        true
+    });
+    outline_styles.retain(&live_styles);
+
+    if outline_styles.dirty {
+        outline_styles
+            .buffer
+            .write_buffer(&render_device, &render_queue);
+        outline_styles.dirty = false;
+    }
 }
 
 #[cfg(test)]
@@ -116,9 +245,102 @@ mod tests {
     };
     use bevy_render::RenderApp;
 
-    use crate::{ExtractedOutline, MeshOutline, MeshOutlinePlugin, OutlineCamera};
+    use crate::{
+        ExtractedOutline, MeshOutline, MeshOutlinePlugin, OutlineCamera, OutlineCorners,
+        OutlineWidth,
+    };
+
+    use super::{NO_LAYER, OutlineBindGroups, OutlineKey, OutlineStyles};
+
+    fn extracted(width: f32) -> ExtractedOutline {
+        ExtractedOutline {
+            width: OutlineWidth::Pixels(width),
+            ..Default::default()
+        }
+    }
+
+    /// Live appearances keep their style slot, and slots of dropped
+    /// appearances are handed out again instead of growing the buffer.
+    #[test]
+    fn style_slots_are_stable_and_reused() {
+        let mut styles = OutlineStyles::default();
+        let a = extracted(1.0);
+        let b = extracted(2.0);
+        let c = extracted(3.0);
+        let (key_a, key_b, key_c) = (
+            OutlineKey::from_outline(&a),
+            OutlineKey::from_outline(&b),
+            OutlineKey::from_outline(&c),
+        );
+
+        let slot_a = styles.slot(&key_a, &a);
+        let slot_b = styles.slot(&key_b, &b);
+        assert_ne!(slot_a, slot_b);
+        assert_eq!(styles.slot(&key_a, &a), slot_a);
+
+        styles.retain(&[key_b.clone()].into_iter().collect());
+        assert_eq!(styles.slot(&key_b, &b), slot_b);
+        assert_eq!(styles.slot(&key_c, &c), slot_a);
+        assert_eq!(styles.buffer.get().len(), 2);
+    }
+
+    /// Meshes outlined alike but filled differently don't share a style, so
+    /// each is drawn with its own fill.
+    #[test]
+    fn fills_are_part_of_the_appearance() {
+        let mut styles = OutlineStyles::default();
+        let plain = extracted(2.0);
+        let filled = ExtractedOutline {
+            fill_color: Vec4::new(0.0, 0.0, 1.0, 0.5),
+            ..extracted(2.0)
+        };
 
-    use super::{OutlineBindGroups, OutlineKey};
+        let plain_slot = styles.slot(&OutlineKey::from_outline(&plain), &plain);
+        let filled_slot = styles.slot(&OutlineKey::from_outline(&filled), &filled);
+        assert_ne!(plain_slot, filled_slot);
+        assert_eq!(styles.buffer.get()[filled_slot as usize].fill_color.w, 0.5);
+    }
+
+    /// Each layer of a stack gets a style slot, linked from the layer below.
+    #[test]
+    fn stacked_layers_link_their_styles() {
+        let mut styles = OutlineStyles::default();
+        let stack = ExtractedOutline {
+            next_layer: Some(Box::new(extracted(8.0))),
+            ..extracted(2.0)
+        };
+        let key = OutlineKey::from_outline(&stack);
+        let top_key = OutlineKey::from_outline(&extracted(8.0));
+
+        let slot = styles.slot(&key, &stack);
+        let top_slot = styles.slot(&top_key, &extracted(8.0));
+        assert_ne!(slot, top_slot);
+
+        let buffer = styles.buffer.get();
+        assert_eq!(buffer[slot as usize].next_layer, top_slot);
+        assert_eq!(buffer[top_slot as usize].next_layer, NO_LAYER);
+    }
+
+    /// The flood measures a stack with the corners that reach the farthest,
+    /// so no layer's band is cut short.
+    #[test]
+    fn stacks_flood_with_their_farthest_reaching_corners() {
+        let mut styles = OutlineStyles::default();
+        let square = ExtractedOutline {
+            corners: OutlineCorners::Square.mode(),
+            ..extracted(8.0)
+        };
+        let stack = ExtractedOutline {
+            corners: OutlineCorners::Diamond.mode(),
+            next_layer: Some(Box::new(square.clone())),
+            ..extracted(2.0)
+        };
+
+        let slot = styles.slot(&OutlineKey::from_outline(&stack), &stack);
+        let style = &styles.buffer.get()[slot as usize];
+        assert_eq!(style.corners, OutlineCorners::Diamond.mode());
+        assert_eq!(style.flood_corners, OutlineCorners::Square.mode());
+    }
 
     /// An outline whose appearance changes every frame must still have a bind
     /// group for that frame's appearance, or `SetOutlineBindGroup` skips it.
@@ -173,9 +395,10 @@ mod tests {
                 assert!(
                     bind_groups
                         .0
-                        .contains_key(&OutlineKey::from_outline(outline)),
+                        .get(&OutlineKey::from_outline(outline))
+                        .is_some_and(|bind_groups| !bind_groups.is_empty()),
                     "frame {frame}: no bind group for the outline being drawn \
-                     (width {}), so its draw is skipped",
+                     (width {:?}), so its draw is skipped",
                     outline.width,
                 );
                 checked += 1;
@@ -217,7 +440,9 @@ mod tests {
 
     fn animate_outline(mut outlines: Query<&mut MeshOutline>) {
         for mut outline in outlines.iter_mut() {
-            outline.width -= 0.25;
+            if let OutlineWidth::Pixels(width) = &mut outline.width {
+                *width -= 0.25;
+            }
         }
     }
 }
@@ -0,0 +1,272 @@
+use bevy::{
+    core_pipeline::{
+        FullscreenShader,
+        prepass::{NormalPrepass, ViewPrepassTextures},
+    },
+    prelude::*,
+    render::{
+        render_resource::{
+            BindGroupEntries, BindGroupLayoutDescriptor, BindGroupLayoutEntries, BindingResource,
+            CachedRenderPipelineId, FragmentState, LoadOp, Operations, PipelineCache,
+            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, StoreOp,
+            binding_types::{
+                texture_2d, texture_2d_multisampled, texture_depth_2d,
+                texture_depth_2d_multisampled, uniform_buffer,
+            },
+        },
+        renderer::RenderContext,
+        texture::CachedTexture,
+    },
+};
+use wgpu_types::{
+    ColorTargetState, ColorWrites, MultisampleState, PrimitiveState, ShaderStages, TextureFormat,
+    TextureSampleType,
+};
+
+use crate::{
+    OutlineCameraSettings, shaders::SCENE_EDGES_SHADER_HANDLE, uniforms::OutlineViewUniform,
+};
+
+/// Format of the scene edge texture, which only stores how much of each pixel
+/// the ink line covers.
+pub const SCENE_EDGE_FORMAT: TextureFormat = TextureFormat::R8Unorm;
+
+/// Finds the edges of the whole scene from the depth and normal prepasses of
+/// views with [`crate::OutlineSceneEdges`].
+#[derive(Clone, Resource)]
+pub struct SceneEdgePipeline {
+    pub layout: BindGroupLayoutDescriptor,
+    /// Layout used when the prepass textures are multisampled (MSAA enabled).
+    pub layout_multisampled: BindGroupLayoutDescriptor,
+    pub pipeline_id: CachedRenderPipelineId,
+    pub pipeline_id_multisampled: CachedRenderPipelineId,
+}
+
+/// Binding 0 holds the prepass depth, binding 1 the prepass normals and
+/// binding 2 the view's [`OutlineViewUniform`].
+fn scene_edge_layout(multisampled: bool) -> BindGroupLayoutDescriptor {
+    let (depth, normal) = if multisampled {
+        (
+            texture_depth_2d_multisampled(),
+            texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+        )
+    } else {
+        (
+            texture_depth_2d(),
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        )
+    };
+
+    BindGroupLayoutDescriptor::new(
+        "outline_scene_edges_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (depth, normal, uniform_buffer::<OutlineViewUniform>(true)),
+        ),
+    )
+}
+
+impl FromWorld for SceneEdgePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = scene_edge_layout(false);
+        let layout_multisampled = scene_edge_layout(true);
+
+        let fullscreen_shader = world.resource::<FullscreenShader>().clone();
+
+        let make_descriptor =
+            |label: &'static str, layout: BindGroupLayoutDescriptor, multisampled: bool| {
+                let shader_defs = if multisampled {
+                    vec!["MULTISAMPLED".into()]
+                } else {
+                    vec![]
+                };
+
+                RenderPipelineDescriptor {
+                    label: Some(label.into()),
+                    layout: vec![layout],
+                    vertex: fullscreen_shader.to_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: SCENE_EDGES_SHADER_HANDLE,
+                        shader_defs,
+                        entry_point: Some("fragment".into()),
+                        targets: vec![Some(ColorTargetState {
+                            format: SCENE_EDGE_FORMAT,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    immediate_size: 0,
+                    zero_initialize_workgroup_memory: false,
+                }
+            };
+
+        let cache = world.resource_mut::<PipelineCache>();
+        let pipeline_id = cache.queue_render_pipeline(make_descriptor(
+            "outline_scene_edges_pipeline",
+            layout.clone(),
+            false,
+        ));
+        let pipeline_id_multisampled = cache.queue_render_pipeline(make_descriptor(
+            "outline_scene_edges_pipeline_msaa",
+            layout_multisampled.clone(),
+            true,
+        ));
+
+        Self {
+            layout,
+            layout_multisampled,
+            pipeline_id,
+            pipeline_id_multisampled,
+        }
+    }
+}
+
+/// Marks a [`NormalPrepass`] that was added for scene edges, so it is only
+/// removed again if the user didn't add it themselves.
+#[derive(Component)]
+pub(crate) struct SceneEdgeNormalPrepass;
+
+/// Adds the [`NormalPrepass`] scene edges are found from to cameras that
+/// enable them, and removes it again once they are turned off.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_normal_prepass(
+    mut commands: Commands,
+    cameras: Query<
+        (
+            Entity,
+            &OutlineCameraSettings,
+            Has<NormalPrepass>,
+            Has<SceneEdgeNormalPrepass>,
+        ),
+        Changed<OutlineCameraSettings>,
+    >,
+    added: Query<(), With<SceneEdgeNormalPrepass>>,
+    mut removed_settings: RemovedComponents<OutlineCameraSettings>,
+) {
+    for (entity, settings, has_prepass, added_prepass) in cameras.iter() {
+        if settings.scene_edges.is_some() && !has_prepass {
+            commands
+                .entity(entity)
+                .insert((NormalPrepass, SceneEdgeNormalPrepass));
+        } else if settings.scene_edges.is_none() && added_prepass {
+            commands
+                .entity(entity)
+                .remove::<(NormalPrepass, SceneEdgeNormalPrepass)>();
+        }
+    }
+
+    for entity in removed_settings.read() {
+        if added.contains(entity) {
+            commands
+                .entity(entity)
+                .remove::<(NormalPrepass, SceneEdgeNormalPrepass)>();
+        }
+    }
+}
+
+/// Renders the scene edges of a view into `target`. The texture is only
+/// cleared until the normal prepass and the pipeline are ready.
+pub(crate) fn draw_scene_edges(
+    world: &World,
+    render_context: &mut RenderContext<'_, '_>,
+    target: &CachedTexture,
+    prepass_textures: &ViewPrepassTextures,
+    multisampled: bool,
+    view_uniforms: BindingResource,
+    view_offset: u32,
+) {
+    let scene_edge_pipeline = world.resource::<SceneEdgePipeline>();
+    let pipeline_cache = world.resource::<PipelineCache>();
+    let (pipeline_id, layout) = if multisampled {
+        (
+            scene_edge_pipeline.pipeline_id_multisampled,
+            &scene_edge_pipeline.layout_multisampled,
+        )
+    } else {
+        (scene_edge_pipeline.pipeline_id, &scene_edge_pipeline.layout)
+    };
+    let pipeline = pipeline_cache.get_render_pipeline(pipeline_id);
+    // Created before the pass, which borrows it until the end of the function.
+    let bind_group = match (
+        prepass_textures.depth_view(),
+        prepass_textures.normal_view(),
+    ) {
+        (Some(depth), Some(normal)) => Some(render_context.render_device().create_bind_group(
+            "outline_scene_edges_bind_group",
+            &pipeline_cache.get_bind_group_layout(layout),
+            &BindGroupEntries::sequential((depth, normal, view_uniforms)),
+        )),
+        _ => None,
+    };
+
+    let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+        label: Some("outline_scene_edges_pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: &target.default_view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(wgpu_types::Color::TRANSPARENT),
+                store: StoreOp::Store,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+        multiview_mask: None,
+    });
+
+    if let (Some(pipeline), Some(bind_group)) = (pipeline, bind_group.as_ref()) {
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, bind_group, &[view_offset]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OutlineSceneEdges;
+
+    /// The prepass added for scene edges goes away with them, while one the
+    /// user added stays.
+    #[test]
+    fn normal_prepass_follows_the_scene_edges() {
+        let mut app = App::new();
+        app.add_systems(Update, update_normal_prepass);
+
+        let with_edges =
+            OutlineCameraSettings::default().with_scene_edges(OutlineSceneEdges::default());
+        let added = app.world_mut().spawn(with_edges.clone()).id();
+        let own = app
+            .world_mut()
+            .spawn((with_edges.clone(), NormalPrepass))
+            .id();
+        app.update();
+
+        assert!(app.world().get::<NormalPrepass>(added).is_some());
+
+        for camera in [added, own] {
+            app.world_mut()
+                .get_mut::<OutlineCameraSettings>(camera)
+                .unwrap()
+                .scene_edges = None;
+        }
+        app.update();
+
+        assert!(app.world().get::<NormalPrepass>(added).is_none());
+        assert!(app.world().get::<NormalPrepass>(own).is_some());
+
+        app.world_mut().entity_mut(added).insert(with_edges);
+        app.update();
+        app.world_mut()
+            .entity_mut(added)
+            .remove::<OutlineCameraSettings>();
+        app.update();
+
+        assert!(app.world().get::<NormalPrepass>(added).is_none());
+    }
+}
@@ -1,13 +1,24 @@
 #import bevy_pbr::{
     view_transformations::{ndc_to_uv},
 }
+#import bevy_mesh_outline::types::{
+    OutlineStyle, OutlineView, OCCLUSION_ALWAYS_ON_TOP, OCCLUSION_XRAY, FALLOFF_LINEAR,
+    FALLOFF_SMOOTHSTEP, FALLOFF_EXPONENTIAL, PLACEMENT_OUTER, PLACEMENT_INNER, BLEND_ALPHA, BLEND_REPLACE,
+    BLEND_ADDITIVE, BLEND_MULTIPLY, BLEND_SCREEN, NO_LAYER, MAX_OUTLINE_LAYERS, MAX_GRADIENT_STOPS, resolve_width,
+    corner_distance, edge_softness, PATTERN_DASHED, PATTERN_DOTTED, pulse_phase, EDGES_SILHOUETTE,
+    interior_width, is_interior_edge, fade_visibility,
+}
+
+// Relative tolerance when comparing the outline depth against the scene depth
+// of the same surface, which comes from a different pass.
+const DEPTH_EPSILON: f32 = 1e-4;
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
 @group(0) @binding(2) var flood_texture: texture_2d<f32>;
 @group(0) @binding(3) var appearance_texture: texture_2d<f32>;
-// Global scene depth from the prepass. When the camera has MSAA enabled this
-// texture is multisampled and must be read with textureLoad.
+// Global scene depth from the prepass, read by pixel. When the camera has MSAA
+// enabled this texture is multisampled.
 #ifdef MULTISAMPLED
 @group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
 #else
@@ -15,41 +26,507 @@
 #endif
 // Outline depth is owned by the plugin and is always single-sampled.
 @group(0) @binding(5) var outline_depth_texture: texture_depth_2d;
+// Per-appearance styles, indexed by the style index in the appearance texture.
+@group(0) @binding(6) var<storage, read> styles: array<OutlineStyle>;
+@group(0) @binding(7) var<uniform> view: OutlineView;
+// Runner-up seeds of the flood, only meaningful when the view keeps two
+// overlapping outlines per pixel.
+@group(0) @binding(8) var secondary_flood_texture: texture_2d<f32>;
+// World normals and view depths of the mask, for finding interior edges.
+@group(0) @binding(9) var edge_texture: texture_2d<f32>;
+// Coverage of the scene edges in red, only meaningful when the view has them.
+@group(0) @binding(10) var scene_edge_texture: texture_2d<f32>;
+
+// Opacity of the outline at normalized distance `t` (0 at the silhouette, 1 at
+// the outer edge of the band). Must match `OutlineFalloff::opacity`.
+fn falloff_alpha(style: OutlineStyle, t: f32) -> f32 {
+    switch style.falloff {
+        case FALLOFF_LINEAR: {
+            return 1.0 - t;
+        }
+        case FALLOFF_SMOOTHSTEP: {
+            return 1.0 - smoothstep(0.0, 1.0, t);
+        }
+        case FALLOFF_EXPONENTIAL: {
+            // Rescaled so the curve still reaches zero at the outer edge.
+            let power = max(style.falloff_power, 1e-4);
+            let edge = exp(-power);
+            return (exp(-power * t) - edge) / (1.0 - edge);
+        }
+        default: {
+            return 1.0;
+        }
+    }
+}
+
+fn gradient_offset(layer: u32, stop: u32) -> f32 {
+    return styles[layer].gradient_offsets[stop / 4u][stop % 4u];
+}
+
+// Color of the band of the style in slot `layer` at normalized distance `t`,
+// from its gradient if it has one, and tinted by its pulse.
+fn band_color(layer: u32, t: f32) -> vec4<f32> {
+    let color = gradient_color(layer, t);
+    let style = styles[layer];
+    // A negative alpha marks pulses that keep the outline's color.
+    if style.pulse_period <= 0.0 || style.pulse_color.a < 0.0 {
+        return color;
+    }
+    return mix(color, style.pulse_color, pulse_phase(style, view));
+}
+
+// Color of the band at `t` from the gradient alone. Must match
+// `OutlineGradient::color_at`.
+fn gradient_color(layer: u32, t: f32) -> vec4<f32> {
+    let len = styles[layer].gradient_len;
+    if len == 0u {
+        return styles[layer].color;
+    }
+    var color = styles[layer].gradient_colors[0];
+    for (var i = 1u; i < min(len, MAX_GRADIENT_STOPS); i++) {
+        let start = gradient_offset(layer, i - 1u);
+        let end = gradient_offset(layer, i);
+        if t >= end {
+            color = styles[layer].gradient_colors[i];
+            continue;
+        }
+        if t > start {
+            let f = (t - start) / max(end - start, 1e-6);
+            color = mix(styles[layer].gradient_colors[i - 1u], styles[layer].gradient_colors[i], f);
+        }
+        break;
+    }
+    return color;
+}
+
+fn hash(p: vec3<f32>) -> f32 {
+    return fract(sin(dot(p, vec3<f32>(127.1, 311.7, 74.7))) * 43758.5453);
+}
+
+// Smooth value noise in [-1, 1].
+fn value_noise(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let x00 = mix(hash(i), hash(i + vec3<f32>(1.0, 0.0, 0.0)), u.x);
+    let x10 = mix(hash(i + vec3<f32>(0.0, 1.0, 0.0)), hash(i + vec3<f32>(1.0, 1.0, 0.0)), u.x);
+    let x01 = mix(hash(i + vec3<f32>(0.0, 0.0, 1.0)), hash(i + vec3<f32>(1.0, 0.0, 1.0)), u.x);
+    let x11 = mix(hash(i + vec3<f32>(0.0, 1.0, 1.0)), hash(i + vec3<f32>(1.0, 1.0, 1.0)), u.x);
+    let value = mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z);
+    return value * 2.0 - 1.0;
+}
+
+// How far the style's noise moves the band edge at `pixel_px`, in flood
+// texture pixels. The noise is laid out in physical screen pixels.
+fn band_noise(style: OutlineStyle, pixel_px: vec2<f32>) -> f32 {
+    let amplitude = style.noise.x * view.resolution_scale;
+    if amplitude == 0.0 {
+        return 0.0;
+    }
+    let screen_px = pixel_px / view.resolution_scale;
+    let p = vec3<f32>(screen_px * style.noise.y, view.time * style.noise.z);
+    return amplitude * value_noise(p);
+}
+
+// Position along the outline of a band pixel at `pixel_px` whose seed is at
+// `seed_px`, in pixels. It is measured along the screen diagonal closest to
+// the outline's direction, which is exact along straight edges, and is the
+// same for every pixel across the band.
+fn outline_position(seed_px: vec2<f32>, pixel_px: vec2<f32>) -> f32 {
+    let offset = pixel_px - seed_px;
+    var normal = vec2<f32>(0.7071, 0.7071);
+    if dot(offset, offset) > 0.25 {
+        normal = normalize(offset);
+    }
+    let tangent = vec2<f32>(-normal.y, normal.x);
+    var axis = vec2<f32>(0.7071, 0.7071);
+    if abs(tangent.x - tangent.y) > abs(tangent.x + tangent.y) {
+        axis = vec2<f32>(0.7071, -0.7071);
+    }
+    return dot(seed_px, axis) / abs(dot(axis, tangent));
+}
+
+// How much of a band pixel at `position` along the outline the style's
+// pattern covers. `width` is the band width, all in flood texture pixels.
+// Must match `OutlinePattern::draws`.
+fn pattern_coverage(style: OutlineStyle, position: f32, width: f32) -> f32 {
+    var dash: f32;
+    var gap: f32;
+    var offset = 0.0;
+    switch style.pattern {
+        case PATTERN_DASHED: {
+            dash = style.dashes.x * view.resolution_scale;
+            gap = style.dashes.y * view.resolution_scale;
+            offset = view.time * style.dashes.z * view.resolution_scale;
+        }
+        case PATTERN_DOTTED: {
+            dash = width;
+            gap = width;
+        }
+        default: {
+            return 1.0;
+        }
+    }
+    let period = dash + gap;
+    if period <= 0.0 {
+        return 1.0;
+    }
+
+    // Signed distance to the nearest dash end, positive inside the dash.
+    let phase = fract((position - offset) / period) * period;
+    let inside = min(phase, dash - phase);
+    let outside = min(phase - dash, period - phase);
+    let distance = select(-outside, inside, phase < dash);
+
+    let softness = edge_softness(view);
+    if softness <= 0.0 {
+        return select(0.0, 1.0, distance > 0.0);
+    }
+    return clamp(distance / softness + 0.5, 0.0, 1.0);
+}
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
 }
 
-@fragment
-fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    var color = textureSample(screen_texture, texture_sampler, in.uv);
-    let flood_data = textureSample(flood_texture, texture_sampler, in.uv);
+// Fraction of the scene depth samples of `pixel` in front of a surface at
+// `surface_depth`, with `epsilon` of relative tolerance. Without anti-aliasing
+// only the first sample counts.
+fn hidden_fraction(pixel: vec2<i32>, surface_depth: f32, epsilon: f32) -> f32 {
+#ifdef MULTISAMPLED
+    let samples = select(1u, textureNumSamples(depth_texture), view.antialias != 0u);
+#else
+    let samples = 1u;
+#endif
+    var hidden = 0u;
+    for (var i = 0u; i < samples; i++) {
+#ifdef MULTISAMPLED
+        let scene_depth = textureLoad(depth_texture, pixel, i32(i));
+#else
+        let scene_depth = textureLoad(depth_texture, pixel, 0);
+#endif
+        if surface_depth < scene_depth * (1.0 - epsilon) {
+            hidden++;
+        }
+    }
+    return f32(hidden) / f32(samples);
+}
+
+// Where an outlined mesh is, relative to the scene, for its contact line.
+const CONTACT_NONE: u32 = 0u;
+const CONTACT_FRONT: u32 = 1u;
+const CONTACT_BEHIND: u32 = 2u;
+
+// Whether the mesh `silhouette` is in front of the scene at the screen
+// `pixel`, behind a surface within `epsilon` of it, or neither.
+fn contact_state(pixel: vec2<i32>, silhouette: f32, epsilon: f32) -> u32 {
+    let screen_dims = vec2<i32>(textureDimensions(screen_texture));
+    let screen_pixel = clamp(pixel, vec2<i32>(0), screen_dims - 1);
+    let uv = (vec2<f32>(screen_pixel) + 0.5) / vec2<f32>(screen_dims);
+    let outline_pixel = vec2<i32>(uv * vec2<f32>(textureDimensions(outline_depth_texture)));
+    let depth = textureLoad(outline_depth_texture, outline_pixel, 0);
+    if depth <= 0.0 || textureLoad(appearance_texture, outline_pixel, 0).z != silhouette {
+        return CONTACT_NONE;
+    }
+    let scene_depth = textureLoad(depth_texture, screen_pixel, 0);
+    if depth >= scene_depth * (1.0 - DEPTH_EPSILON) {
+        return CONTACT_FRONT;
+    }
+    if depth >= scene_depth * (1.0 - epsilon) {
+        return CONTACT_BEHIND;
+    }
+    return CONTACT_NONE;
+}
+
+// Coverage of the screen `pixel` by the contact line of the mesh
+// `silhouette`: whether parts of it in front of and just behind the scene meet
+// within half the line `width` of the pixel.
+fn contact_coverage(pixel: vec2<i32>, silhouette: f32, width: f32, epsilon: f32) -> f32 {
+    let center = contact_state(pixel, silhouette, epsilon);
+    if center == CONTACT_NONE {
+        return 0.0;
+    }
+    let reach = i32(max(round(width * 0.5), 1.0));
+    let offsets = array<vec2<i32>, 4>(
+        vec2<i32>(1, 0),
+        vec2<i32>(-1, 0),
+        vec2<i32>(0, 1),
+        vec2<i32>(0, -1),
+    );
+    for (var i = 0; i < 4; i++) {
+        let neighbor = contact_state(pixel + offsets[i] * reach, silhouette, epsilon);
+        if neighbor != CONTACT_NONE && neighbor != center {
+            return 1.0;
+        }
+    }
+    return 0.0;
+}
+
+// Color to draw for a surface using the given occlusion mode, where `occluded`
+// is the fraction of the pixel hiding it. Modes that hide the surface scale
+// its alpha down.
+fn apply_occlusion(
+    mode: u32,
+    color: vec4<f32>,
+    occluded_color: vec4<f32>,
+    occluded: f32,
+) -> vec4<f32> {
+    switch mode {
+        case OCCLUSION_ALWAYS_ON_TOP: {
+            return color;
+        }
+        case OCCLUSION_XRAY: {
+            return mix(color, occluded_color, occluded);
+        }
+        default: {
+            return vec4<f32>(color.rgb, color.a * (1.0 - occluded));
+        }
+    }
+}
+
+// Coverage of a pixel `distance` from the seed by a band of `width`, which
+// fades out over `softness`.
+fn edge_coverage(distance: f32, width: f32, softness: f32) -> f32 {
+    if softness <= 0.0 {
+        return select(0.0, 1.0, distance < width);
+    }
+    return 1.0 - smoothstep(width - softness * 0.5, width + softness * 0.5, distance);
+}
+
+// Whether `seed_uv` is a seed of the silhouette `silhouette`.
+fn is_silhouette_seed(seed_uv: vec2<f32>, silhouette: f32) -> bool {
+    if seed_uv.x <= 0.0 || seed_uv.y <= 0.0 {
+        return false;
+    }
+    let dims = vec2<f32>(textureDimensions(appearance_texture));
+    return textureLoad(appearance_texture, vec2<i32>(seed_uv * dims), 0).z == silhouette;
+}
+
+// Nearest seed of `silhouette` to the position `px` in the flood textures,
+// among the best and runner-up seeds there, or a negative uv if neither is.
+fn silhouette_seed(px: vec2<f32>, silhouette: f32) -> vec2<f32> {
+    let dims = vec2<f32>(textureDimensions(flood_texture));
+    if any(px < vec2<f32>(0.0)) || any(px >= dims) {
+        return vec2<f32>(-1.0);
+    }
+    let pixel = vec2<i32>(px);
+    let best = textureLoad(flood_texture, pixel, 0).xy;
+    if is_silhouette_seed(best, silhouette) {
+        return best;
+    }
+    if view.overlap_candidates > 1u {
+        let second = textureLoad(secondary_flood_texture, pixel, 0).xy;
+        if is_silhouette_seed(second, silhouette) {
+            return second;
+        }
+    }
+    return vec2<f32>(-1.0);
+}
+
+// Composites `src` onto `dst`. `coverage` is how much of the pixel the outline
+// covers (its falloff), `opacity` how strongly it's applied where it does.
+fn blend(mode: u32, dst: vec4<f32>, src: vec3<f32>, coverage: f32, opacity: f32) -> vec4<f32> {
+    var alpha = clamp(coverage * opacity, 0.0, 1.0);
+    var rgb: vec3<f32>;
+    switch mode {
+        case BLEND_REPLACE: {
+            alpha = clamp(coverage, 0.0, 1.0);
+            rgb = mix(dst.rgb, src, alpha);
+        }
+        case BLEND_ADDITIVE: {
+            rgb = dst.rgb + src * alpha;
+        }
+        case BLEND_MULTIPLY: {
+            rgb = mix(dst.rgb, dst.rgb * src, alpha);
+        }
+        case BLEND_SCREEN: {
+            rgb = mix(dst.rgb, dst.rgb + src - dst.rgb * src, alpha);
+        }
+        default: {
+            rgb = mix(dst.rgb, src, alpha);
+        }
+    }
+    return vec4<f32>(rgb, mix(dst.a, 1.0, alpha));
+}
+
+// Draws the outline layers of the seed in `flood_data` over `color`, for the
+// screen `pixel` at `uv` whose own outline depth and appearance are given.
+fn draw_outline(
+    color_in: vec4<f32>,
+    flood_data: vec4<f32>,
+    uv: vec2<f32>,
+    pixel: vec2<i32>,
+    pixel_depth: f32,
+    pixel_appearance: vec4<f32>,
+) -> vec4<f32> {
+    var color = color_in;
     let seed_uv = flood_data.xy;
 
-    // Early return if no outline data
+    // No outline data
     if seed_uv.x <= 0.0 || seed_uv.y <= 0.0 {
         return color;
     }
 
-    // Get depths
-#ifdef MULTISAMPLED
-    // Multisampled depth textures can't be sampled; read sample 0 by pixel.
-    let current_depth = textureLoad(depth_texture, vec2<i32>(in.clip_position.xy), 0);
-#else
-    let current_depth = textureSample(depth_texture, texture_sampler, in.uv);
-#endif
     let outline_depth = textureSample(outline_depth_texture, texture_sampler, seed_uv);
 
     // Get appearance data for this outline
     let appearance = textureSample(appearance_texture, texture_sampler, seed_uv);
-    let outline_color = appearance.rgb;
-    
-    // Only render outline when it's behind the current geometry
-    if outline_depth > current_depth {
-        // Apply outline color
-        color = vec4<f32>(outline_color, 1.0);
-    }
-    
+
+    // Whether this pixel lies inside the silhouette of the seed's own mesh or
+    // outline group, which decides if it can be part of an inner or an outer
+    // band. Must match `OutlinePlacement::band_width`.
+    let covered = pixel_depth > 0.0;
+    let inside = covered && pixel_appearance.z == appearance.z;
+
+    // The flood only propagates seeds within their widest band, but the seeds
+    // themselves are kept regardless of it.
+    let dims = vec2<f32>(textureDimensions(flood_texture));
+    let seed_pixel = vec2<i32>(seed_uv * dims);
+
+    // Inner bands lie on the mesh's own surface; outer bands are occluded when
+    // scene geometry is in front of their seed.
+    var occluded: f32;
+    if inside {
+        occluded = hidden_fraction(pixel, pixel_depth, DEPTH_EPSILON);
+    } else {
+        occluded = hidden_fraction(pixel, outline_depth, 0.0);
+    }
+
+    // Draw the mesh's outline layers in order, each over the previous ones.
+    var layer = u32(appearance.x);
+    for (var i = 0u; i < MAX_OUTLINE_LAYERS && layer != NO_LAYER; i++) {
+        let slot = layer;
+        let style = styles[slot];
+        layer = style.next_layer;
+
+        // Seeds on an interior edge draw a line centered on it, over the
+        // mesh. Other seeds are on the silhouette.
+        let interior = inside
+            && is_interior_edge(edge_texture, appearance_texture, seed_pixel, style.edges, style.crease_cos);
+        if !interior && ((style.edges & EDGES_SILHOUETTE) == 0u
+            || (inside && style.placement == PLACEMENT_OUTER)
+            || (!inside && style.placement == PLACEMENT_INNER)) {
+            continue;
+        }
+
+        // Width of this layer's band on the seed's side of the edge, measured
+        // with the layer's own corners.
+        var width = resolve_width(style, view, appearance.w);
+        if interior {
+            width = interior_width(style, width);
+        }
+
+        // An offset band is the band around the silhouette moved on screen:
+        // it's measured from where the pixel was before the move, to the
+        // nearest seed of the same silhouette there.
+        var band_px = uv * dims;
+        var band_seed_px = seed_uv * dims;
+        var softness = edge_softness(view);
+        if !interior && any(style.offset != vec2<f32>(0.0)) {
+            band_px -= style.offset * view.resolution_scale;
+            let band_seed_uv = silhouette_seed(band_px, appearance.z);
+            if band_seed_uv.x < 0.0 {
+                continue;
+            }
+            band_seed_px = band_seed_uv * dims;
+            softness = max(softness, style.offset_blur * view.resolution_scale);
+        }
+
+        // Noise moves the edge in or out, so the band wobbles.
+        let seed_distance = corner_distance(style.corners, band_px, band_seed_px)
+            - band_noise(style, band_px);
+        let coverage = edge_coverage(seed_distance, width, softness);
+        if coverage <= 0.0 {
+            continue;
+        }
+
+        let t = clamp(seed_distance / width, 0.0, 1.0);
+        let outline_color = apply_occlusion(
+            style.occlusion,
+            band_color(slot, t),
+            style.occluded_color,
+            occluded,
+        );
+        if outline_color.a <= 0.0 {
+            continue;
+        }
+
+        let position = outline_position(band_seed_px, band_px);
+        let pattern = pattern_coverage(style, position, width);
+        let alpha = clamp(falloff_alpha(style, t), 0.0, 1.0) * coverage * pattern;
+        let opacity = outline_color.a * fade_visibility(style, view);
+        color = blend(style.blend_mode, color, outline_color.rgb, alpha, opacity);
+    }
+
     return color;
-}
\ No newline at end of file
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(screen_texture, texture_sampler, in.uv);
+
+    let pixel = vec2<i32>(in.clip_position.xy);
+
+    // The outlined mesh covering this pixel, if any.
+    let pixel_depth = textureSample(outline_depth_texture, texture_sampler, in.uv);
+    let pixel_appearance = textureSample(appearance_texture, texture_sampler, in.uv);
+    let covered = pixel_depth > 0.0;
+
+    if covered {
+        let hidden = hidden_fraction(pixel, pixel_depth, DEPTH_EPSILON);
+        var layer = u32(pixel_appearance.x);
+        for (var i = 0u; i < MAX_OUTLINE_LAYERS && layer != NO_LAYER; i++) {
+            let style = styles[layer];
+            layer = style.next_layer;
+            // Fading scales the opacity of everything the layer draws.
+            let fade = fade_visibility(style, view);
+
+            // X-ray: show the parts of the mesh hidden behind other geometry.
+            if style.occlusion == OCCLUSION_XRAY && hidden > 0.0 {
+                let occluded_color = style.occluded_color;
+                color = blend(style.blend_mode, color, occluded_color.rgb, hidden, occluded_color.a * fade);
+            }
+
+            // Fill: tint the mesh's own pixels. fill_color.a is the fill
+            // opacity, zero when the outline has no fill.
+            if style.fill_color.a > 0.0 {
+                let fill = apply_occlusion(
+                    style.fill_occlusion,
+                    vec4<f32>(style.fill_color.rgb, 1.0),
+                    style.fill_occluded_color,
+                    hidden,
+                );
+                color = blend(style.blend_mode, color, fill.rgb, fill.a, style.fill_color.a * fade);
+            }
+
+            // Contact line where the mesh cuts through other geometry.
+            // contact_color is transparent when the outline has none.
+            if style.contact_color.a > 0.0 {
+                let contact = contact_coverage(
+                    pixel,
+                    pixel_appearance.z,
+                    style.contact_width * fade,
+                    style.contact_epsilon,
+                );
+                color = blend(style.blend_mode, color, style.contact_color.rgb, contact, style.contact_color.a * fade);
+            }
+        }
+    }
+
+    // Ink lines along the edges of the whole scene, below the outlines.
+    if view.scene_edge_color.a > 0.0 {
+        let ink = textureSample(scene_edge_texture, texture_sampler, in.uv).r;
+        color = blend(BLEND_ALPHA, color, view.scene_edge_color.rgb, ink, view.scene_edge_color.a);
+    }
+
+    // The runner-up outline goes below the one that won the pixel.
+    if view.overlap_candidates > 1u {
+        let runner_up = textureSample(secondary_flood_texture, texture_sampler, in.uv);
+        color = draw_outline(color, runner_up, in.uv, pixel, pixel_depth, pixel_appearance);
+    }
+    let flood_data = textureSample(flood_texture, texture_sampler, in.uv);
+    color = draw_outline(color, flood_data, in.uv, pixel, pixel_depth, pixel_appearance);
+
+    return color;
+}
@@ -1,4 +1,9 @@
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_mesh_outline::types::{
+    OutlineStyle, OutlineView, PLACEMENT_OUTER, NO_LAYER, MAX_OUTLINE_LAYERS, PRIORITY_DEPTH_FIRST,
+    PRIORITY_BLEND, EDGES_INTERIOR, resolve_width, corner_distance, edge_softness,
+    is_interior_edge,
+}
 
 // Jump Flood Algorithm for outline distance field generation
 // Iteratively propagates outline seed information across the texture
@@ -14,72 +19,239 @@ struct JumpFloodUniform {
 @group(0) @binding(3) var depth_texture: texture_depth_2d;
 @group(0) @binding(4) var color_texture: texture_2d<f32>;
 @group(0) @binding(5) var appearance_texture: texture_2d<f32>;
+@group(0) @binding(6) var<storage, read> styles: array<OutlineStyle>;
+@group(0) @binding(7) var<uniform> view: OutlineView;
+// Runner-up seeds of the previous pass. Only read by the LAYERED variant,
+// which keeps the two best seeds of different silhouettes per pixel.
+@group(0) @binding(8) var secondary_flood_texture: texture_2d<f32>;
+// World normals and view depths of the mask, for finding interior edges.
+@group(0) @binding(9) var edge_texture: texture_2d<f32>;
+
+const NO_SEED: vec4<f32> = vec4<f32>(-1.0, -1.0, -1.0, 0.0);
+
+struct FloodOutput {
+    @location(0) best: vec4<f32>,
+#ifdef LAYERED
+    @location(1) second: vec4<f32>,
+#endif
+}
 
-fn calculate_priority(candidate_depth: f32, mesh_priority: f32) -> f32 {
-    let depth_factor = (1.0 - candidate_depth) * 10.0;  // Closer is better
-    return depth_factor + mesh_priority;
+// A seed competing for a pixel.
+struct Candidate {
+    seed: vec4<f32>,
+    // Integer `MeshOutline::priority` of the seed's mesh.
+    priority: f32,
+    silhouette: f32,
+    distance: f32,
 }
 
+fn is_seed(c: Candidate) -> bool {
+    return c.seed.x >= 0.0;
+}
+
+// Whether `a` wins a pixel over `b`, a seed of another silhouette, following
+// the view's `OutlinePriority`. Depths are reverse-Z, so the closer seed has
+// the greater one.
+fn outranks(a: Candidate, b: Candidate) -> bool {
+    if !is_seed(a) || !is_seed(b) {
+        return is_seed(a);
+    }
+    let a_depth = a.seed.w;
+    let b_depth = b.seed.w;
+    switch view.priority_mode {
+        case PRIORITY_DEPTH_FIRST: {
+            if a_depth != b_depth {
+                return a_depth > b_depth;
+            }
+            if a.priority != b.priority {
+                return a.priority > b.priority;
+            }
+        }
+        case PRIORITY_BLEND: {
+            let a_rank = a.priority + a_depth * view.depth_weight;
+            let b_rank = b.priority + b_depth * view.depth_weight;
+            if a_rank != b_rank {
+                return a_rank > b_rank;
+            }
+        }
+        default: {
+            if a.priority != b.priority {
+                return a.priority > b.priority;
+            }
+            if a_depth != b_depth {
+                return a_depth > b_depth;
+            }
+        }
+    }
+    // Still tied: prefer the lowest silhouette id, outline groups first and
+    // then meshes in entity order, so the winner doesn't depend on which
+    // neighbor was sampled first or change from frame to frame.
+    return a.silhouette < b.silhouette;
+}
+
+fn flood_output(best: vec4<f32>, second: vec4<f32>) -> FloodOutput {
+    var output: FloodOutput;
+    output.best = best;
+#ifdef LAYERED
+    output.second = second;
+#endif
+    return output;
+}
+
+// Turns the mask into flood seeds, carrying the widest band of the mesh's
+// outline layers. Outer outlines are seeded by every covered pixel, while
+// inner and centered outlines are only seeded by the pixels on their
+// silhouette's boundary, so the flood also measures the distance to the
+// silhouette edge for pixels inside the mesh. Outlines with interior edges are
+// seeded by the pixels on those edges too.
 @fragment
-fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+fn seed(in: FullscreenVertexOutput) -> FloodOutput {
     let dims = vec2<f32>(textureDimensions(flood_texture));
-    let step = i32(instance.step_length);
 
     let current = textureSample(flood_texture, texture_sampler, in.uv);
-    var best_candidate = current;
-    var best_priority = -999999.0;
-    
-    // If current pixel has valid seed data, calculate its priority
-    if (current.x >= 0.0) {
-        let current_depth = current.w;
-        let current_appearance = textureSample(appearance_texture, texture_sampler, current.xy);
-        let current_mesh_priority = floor(current_appearance.w);  // Extract integer priority from packed float
-        best_priority = calculate_priority(current_depth, current_mesh_priority);
+    if (current.x < 0.0) {
+        return flood_output(NO_SEED, NO_SEED);
+    }
+
+    let appearance = textureSample(appearance_texture, texture_sampler, in.uv);
+    var width = 0.0;
+    var all_outer = true;
+    var interior = false;
+    var layer = u32(appearance.x);
+    for (var i = 0u; i < MAX_OUTLINE_LAYERS && layer != NO_LAYER; i++) {
+        let style = styles[layer];
+        // Noise can widen the band by its amplitude, and an offset moves it
+        // away from the silhouette, blurred edge included. Must match
+        // `band_spread` in `flood.rs`.
+        let spread = (style.noise.x + length(style.offset) + style.offset_blur * 0.5)
+            * view.resolution_scale;
+        width = max(width, resolve_width(style, view, appearance.w) + spread);
+        all_outer = all_outer && style.placement == PLACEMENT_OUTER;
+        interior = interior || (style.edges & EDGES_INTERIOR) != 0u;
+        layer = style.next_layer;
+    }
+    // Anti-aliased bands fade out half their edge softness past the width.
+    let seed = vec4<f32>(current.xy, width + edge_softness(view) * 0.5, current.w);
+    if (all_outer && !interior) {
+        return flood_output(seed, NO_SEED);
+    }
+
+    // A pixel is on the boundary if a direct neighbor isn't covered by the
+    // same silhouette.
+    let offsets = array<vec2<f32>, 4>(
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(-1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, -1.0),
+    );
+    for (var i = 0; i < 4; i++) {
+        let neighbor_uv = in.uv + offsets[i] / dims;
+        let neighbor = textureSample(flood_texture, texture_sampler, neighbor_uv);
+        let neighbor_appearance = textureSample(appearance_texture, texture_sampler, neighbor_uv);
+        if (neighbor.x < 0.0 || neighbor_appearance.z != appearance.z) {
+            return flood_output(seed, NO_SEED);
+        }
     }
 
+    let pixel = vec2<i32>(in.position.xy);
+    layer = u32(appearance.x);
+    for (var i = 0u; i < MAX_OUTLINE_LAYERS && layer != NO_LAYER; i++) {
+        let style = styles[layer];
+        if is_interior_edge(edge_texture, appearance_texture, pixel, style.edges, style.crease_cos) {
+            return flood_output(seed, NO_SEED);
+        }
+        layer = style.next_layer;
+    }
+
+    return flood_output(NO_SEED, NO_SEED);
+}
+
+fn candidate(seed: vec4<f32>, uv: vec2<f32>, dims: vec2<f32>) -> Candidate {
+    if (seed.x < 0.0) {
+        return Candidate(NO_SEED, 0.0, 0.0, 0.0);
+    }
+    let appearance = textureSample(appearance_texture, texture_sampler, seed.xy);
+    let style = styles[u32(appearance.x)];
+    return Candidate(
+        seed,
+        appearance.y,
+        appearance.z,
+        corner_distance(style.flood_corners, uv * dims, seed.xy * dims),
+    );
+}
+
+// Offers `c` to the pixel's best (and in the LAYERED variant, runner-up)
+// candidates. Seeds of the same silhouette (a mesh or an outline group)
+// compete on distance, so the flood yields its nearest point. Otherwise the
+// candidate that `outranks` the other wins.
+fn consider(best: ptr<function, Candidate>, second: ptr<function, Candidate>, c: Candidate) {
+    if (is_seed(*best) && c.silhouette == (*best).silhouette) {
+        if (c.distance < (*best).distance) {
+            *best = c;
+        }
+#ifdef LAYERED
+    } else if (is_seed(*second) && c.silhouette == (*second).silhouette) {
+        if (c.distance < (*second).distance) {
+            *second = c;
+        }
+#endif
+    } else if (outranks(c, *best)) {
+#ifdef LAYERED
+        *second = *best;
+#endif
+        *best = c;
+#ifdef LAYERED
+    } else if (outranks(c, *second)) {
+        *second = c;
+#endif
+    }
+
+#ifdef LAYERED
+    // Nearer seeds of the same silhouette can change the order.
+    if (outranks(*second, *best)) {
+        let swap = *best;
+        *best = *second;
+        *second = swap;
+    }
+#endif
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> FloodOutput {
+    let dims = vec2<f32>(textureDimensions(flood_texture));
+    let step = i32(instance.step_length);
+
+    // The pixel's own seeds are kept whatever their distance.
+    var best = candidate(textureSample(flood_texture, texture_sampler, in.uv), in.uv, dims);
+    var second = candidate(NO_SEED, in.uv, dims);
+#ifdef LAYERED
+    consider(&best, &second, candidate(textureSample(secondary_flood_texture, texture_sampler, in.uv), in.uv, dims));
+#endif
+
     // Check all 8 neighbors
     for (var dy = -1; dy <= 1; dy++) {
         for (var dx = -1; dx <= 1; dx++) {
             if (dx == 0 && dy == 0) {
                 continue;
             }
-            
+
             // Sample neighbors at current step distance (starts large, gets smaller each pass)
             let offset = vec2<f32>(f32(dx * step), f32(dy * step)) / dims;
             let neighbor_uv = in.uv + offset;
-            let neighbor = textureSample(flood_texture, texture_sampler, neighbor_uv);
-            
-            // Skip invalid neighbors
-            if (neighbor.x < 0.0) {
-                continue;
-            }
 
-            let seed_pos = neighbor.xy;
-            let outline_width = neighbor.z;
-            let seed_depth = neighbor.w;
-            
-            // Calculate distance from current pixel to seed
-            let dist = distance(in.uv * dims, seed_pos * dims);
-            
-            // Only consider pixels within outline range
-            if (dist >= outline_width) {
-                continue;
+            // Only consider seeds within their outline range
+            let neighbor = candidate(textureSample(flood_texture, texture_sampler, neighbor_uv), in.uv, dims);
+            if (neighbor.seed.x >= 0.0 && neighbor.distance < neighbor.seed.z) {
+                consider(&best, &second, neighbor);
             }
-
-            // Get appearance data for this seed
-            let appearance = textureSample(appearance_texture, texture_sampler, seed_pos);
-            let mesh_priority = floor(appearance.w);  // Extract integer priority from packed float
-            
-            // Calculate priority for this candidate
-            let candidate_priority = calculate_priority(seed_depth, mesh_priority);
-            
-            // Update best candidate if this one has higher priority
-            if (candidate_priority > best_priority) {
-                best_candidate = neighbor;
-                best_priority = candidate_priority;
+#ifdef LAYERED
+            let runner_up = candidate(textureSample(secondary_flood_texture, texture_sampler, neighbor_uv), in.uv, dims);
+            if (runner_up.seed.x >= 0.0 && runner_up.distance < runner_up.seed.z) {
+                consider(&best, &second, runner_up);
             }
+#endif
         }
     }
 
-    return best_candidate;
-}
\ No newline at end of file
+    return flood_output(best.seed, second.seed);
+}
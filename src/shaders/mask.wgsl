@@ -4,28 +4,38 @@
     skinning,
     morph::morph,
     forward_io::{Vertex},
-    view_transformations::{position_world_to_clip, ndc_to_uv, position_world_to_ndc,frag_coord_to_uv},
+    view_transformations::{position_world_to_clip, ndc_to_uv, position_world_to_ndc},
+    mesh_view_bindings::view,
 }
+#import bevy_mesh_outline::types::OutlineView
 
 struct Instance {
-    intensity: f32,
-    width: f32,
     priority: f32,
-    _padding: f32,
-    outline_color: vec3<f32>,
+    style_index: u32,
+    // Outline group plus one, or zero outside of any group.
+    group: u32,
+    // Rank of the entity among the outlined ones, in `Entity` order.
+    tie_break: u32,
 };
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) world_position: vec4<f32>,
+    @location(1) @interpolate(flat) instance_index: u32,
+    @location(2) @interpolate(flat) world_scale: f32,
+    // Zero for meshes without normals.
+    @location(3) world_normal: vec3<f32>,
+    @location(4) view_depth: f32,
 };
 
 struct FragmentOutput {
     @location(0) flood_data: vec4<f32>,
     @location(1) appearance_data: vec4<f32>,
+    @location(2) edge_data: vec4<f32>,
 }
 
 @group(3) @binding(0) var<uniform> outline_instance: Instance;
+@group(3) @binding(1) var<uniform> outline_view: OutlineView;
 
 
 #ifdef MORPH_TARGETS
@@ -53,6 +63,19 @@ fn morph_vertex(vertex_in: Vertex) -> Vertex {
 #endif
 
 
+// Pixels per world unit at the depth of the entity's origin, which converts
+// world unit widths to pixels. Using the origin gives the whole mesh one width.
+// Origins closer than the near plane, or behind the camera, count as on it.
+fn world_scale(instance_index: u32) -> f32 {
+    let world_from_local = mesh_functions::get_world_from_local(instance_index);
+    let origin = position_world_to_clip(world_from_local[3].xyz);
+    let pixels_per_unit = view.clip_from_view[1][1] * 0.5 * view.viewport.w;
+    // Clip w on the near plane: its distance under a perspective projection,
+    // one under an orthographic one. Must match `near_clip_w` in `flood.rs`.
+    let near_w = select(view.clip_from_view[3][2], 1.0, view.clip_from_view[3][3] == 1.0);
+    return pixels_per_unit / max(origin.w, near_w);
+}
+
 @vertex
 fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
     var out: VertexOutput;
@@ -74,21 +97,52 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
     #ifdef VERTEX_POSITIONS
         out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
         out.position = position_world_to_clip(out.world_position.xyz);
+        out.view_depth = -(view.view_from_world * out.world_position).z;
+    #endif
+
+    #ifdef VERTEX_NORMALS
+        #ifdef SKINNED
+            out.world_normal = skinning::skin_normals(world_from_local, vertex.normal);
+        #else
+            out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal, vertex_no_morph.instance_index);
+        #endif
     #endif
+    out.instance_index = vertex_no_morph.instance_index;
+    out.world_scale = world_scale(vertex_no_morph.instance_index);
 
     return out;
 }
 
 @fragment
 fn fragment(vertex: VertexOutput) -> FragmentOutput {
-    let uv = frag_coord_to_uv(vertex.position.xy);
+    // Seeds are uvs of the outline textures, which are smaller than the view
+    // with a resolution scale below one and span the whole render target.
+    let uv = vertex.position.xy / outline_view.texture_size;
     let depth = vertex.position.z;
     
     var output: FragmentOutput;
-    // RT0: seed_uv.xy, outline_width, depth
-    output.flood_data = vec4<f32>(uv, outline_instance.width, depth);
-    // RT1: outline_color.rgb, priority
-    output.appearance_data = vec4<f32>(outline_instance.outline_color * outline_instance.intensity, outline_instance.priority);
+    // RT0: seed_uv.xy, outline width (resolved by the flood seed pass), depth
+    output.flood_data = vec4<f32>(uv, 0.0, depth);
+    // RT1: style_index, priority, silhouette id, world scale. Meshes of the
+    // same outline group share a (negative) id so they form one silhouette;
+    // other meshes are identified by their entity's rank, which also orders
+    // them when their outlines tie.
+    var silhouette = f32(outline_instance.tie_break);
+    if outline_instance.group != 0u {
+        silhouette = -f32(outline_instance.group);
+    }
+    output.appearance_data = vec4<f32>(
+        f32(outline_instance.style_index),
+        outline_instance.priority,
+        silhouette,
+        vertex.world_scale,
+    );
+    // RT2: world normal and view depth, for finding interior edges.
+    var normal = vertex.world_normal;
+    if dot(normal, normal) > 0.0 {
+        normal = normalize(normal);
+    }
+    output.edge_data = vec4<f32>(normal, vertex.view_depth);
 
     return output;
 }
\ No newline at end of file
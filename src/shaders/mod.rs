@@ -2,6 +2,9 @@ use bevy::prelude::*;
 
 use bevy::asset::{Handle, load_internal_asset, uuid_handle};
 
+pub(crate) const TYPES_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("d0b00207-064a-498d-8f57-d8455ca6ed0a");
+
 pub(crate) const MASK_SHADER_HANDLE: Handle<Shader> =
     uuid_handle!("4c41a7eb-b802-4e76-97f1-3327d80743dd");
 
@@ -11,7 +14,11 @@ pub(crate) const FLOOD_SHADER_HANDLE: Handle<Shader> =
 pub(crate) const COMPOSE_SHADER_HANDLE: Handle<Shader> =
     uuid_handle!("6fe0f3ef-e31f-40e7-a20a-ed002ac4bb3f");
 
+pub(crate) const SCENE_EDGES_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("3b9e51c4-7f2a-4d86-b0c5-92e1a6d4f8e3");
+
 pub(crate) fn load_shaders(app: &mut App) {
+    load_internal_asset!(app, TYPES_SHADER_HANDLE, "types.wgsl", Shader::from_wgsl);
     load_internal_asset!(app, MASK_SHADER_HANDLE, "mask.wgsl", Shader::from_wgsl);
     load_internal_asset!(app, FLOOD_SHADER_HANDLE, "flood.wgsl", Shader::from_wgsl);
     load_internal_asset!(
@@ -20,4 +27,10 @@ pub(crate) fn load_shaders(app: &mut App) {
         "compose_output.wgsl",
         Shader::from_wgsl
     );
+    load_internal_asset!(
+        app,
+        SCENE_EDGES_SHADER_HANDLE,
+        "scene_edges.wgsl",
+        Shader::from_wgsl
+    );
 }
@@ -0,0 +1,65 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_mesh_outline::types::OutlineView
+
+// Finds the edges of everything the camera sees from its depth and normal
+// prepasses, for `OutlineSceneEdges`. Writes how much of each pixel of the
+// outline textures the ink line covers to the red channel.
+
+// Prepass textures, multisampled when the camera has MSAA enabled. Only the
+// first sample is read, which keeps the pass to one comparison per neighbor
+// but leaves the lines aliased.
+#ifdef MULTISAMPLED
+@group(0) @binding(0) var depth_texture: texture_depth_multisampled_2d;
+@group(0) @binding(1) var normal_texture: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(0) var depth_texture: texture_depth_2d;
+@group(0) @binding(1) var normal_texture: texture_2d<f32>;
+#endif
+@group(0) @binding(2) var<uniform> view: OutlineView;
+
+fn load_depth(pixel: vec2<i32>) -> f32 {
+    return textureLoad(depth_texture, pixel, 0);
+}
+
+// The prepass stores world normals remapped to [0, 1].
+fn load_normal(pixel: vec2<i32>) -> vec3<f32> {
+    return textureLoad(normal_texture, pixel, 0).xyz * 2.0 - 1.0;
+}
+
+// Whether the prepass pixels `a` and `b` lie on either side of an edge.
+fn is_edge(a: vec2<i32>, b: vec2<i32>) -> bool {
+    let depth_a = load_depth(a);
+    let depth_b = load_depth(b);
+    // Depths are reverse-Z, zero where nothing was drawn.
+    if max(depth_a, depth_b) <= 0.0 {
+        return false;
+    }
+    // With a perspective projection, the relative jump in distance.
+    let jump = abs(depth_a - depth_b) / max(min(depth_a, depth_b), 1e-6);
+    if jump > view.scene_edge_depth_threshold {
+        return true;
+    }
+    return dot(load_normal(a), load_normal(b)) < view.scene_edge_normal_cos;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let dims = vec2<i32>(textureDimensions(depth_texture));
+    let pixel = vec2<i32>(in.uv * vec2<f32>(dims));
+
+    // Pixels find edges up to half the line width away on either side.
+    let reach = i32(max(round(view.scene_edge_width * 0.5), 1.0));
+    let offsets = array<vec2<i32>, 4>(
+        vec2<i32>(1, 0),
+        vec2<i32>(-1, 0),
+        vec2<i32>(0, 1),
+        vec2<i32>(0, -1),
+    );
+    for (var i = 0; i < 4; i++) {
+        let neighbor = clamp(pixel + offsets[i] * reach, vec2<i32>(0), dims - 1);
+        if is_edge(pixel, neighbor) {
+            return vec4<f32>(1.0, 0.0, 0.0, 0.0);
+        }
+    }
+    return vec4<f32>(0.0);
+}
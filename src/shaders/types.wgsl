@@ -0,0 +1,259 @@
+#define_import_path bevy_mesh_outline::types
+
+// Must match `OutlineOcclusion::mode`.
+const OCCLUSION_VISIBLE: u32 = 0u;
+const OCCLUSION_ALWAYS_ON_TOP: u32 = 1u;
+const OCCLUSION_XRAY: u32 = 2u;
+
+// Must match `OutlineFalloff::mode`.
+const FALLOFF_SOLID: u32 = 0u;
+const FALLOFF_LINEAR: u32 = 1u;
+const FALLOFF_SMOOTHSTEP: u32 = 2u;
+const FALLOFF_EXPONENTIAL: u32 = 3u;
+
+// Must match `OutlinePattern::mode`.
+const PATTERN_SOLID: u32 = 0u;
+const PATTERN_DASHED: u32 = 1u;
+const PATTERN_DOTTED: u32 = 2u;
+
+// Must match `OutlinePlacement::mode`.
+const PLACEMENT_OUTER: u32 = 0u;
+const PLACEMENT_INNER: u32 = 1u;
+const PLACEMENT_CENTERED: u32 = 2u;
+
+// Must match `OutlineCorners::mode`.
+const CORNERS_ROUND: u32 = 0u;
+const CORNERS_SQUARE: u32 = 1u;
+const CORNERS_DIAMOND: u32 = 2u;
+
+// Must match `OutlineEdges::flags`.
+const EDGES_SILHOUETTE: u32 = 1u;
+const EDGES_CREASES: u32 = 2u;
+const EDGES_DEPTH: u32 = 4u;
+const EDGES_INTERIOR: u32 = 6u;
+
+// Relative jump in view depth between neighboring pixels of a mesh past which
+// one part of it is in front of another. Must match `DEPTH_DISCONTINUITY`.
+const DEPTH_DISCONTINUITY: f32 = 0.03;
+
+// Must match `OutlineBlendMode::mode`.
+const BLEND_ALPHA: u32 = 0u;
+const BLEND_REPLACE: u32 = 1u;
+const BLEND_ADDITIVE: u32 = 2u;
+const BLEND_MULTIPLY: u32 = 3u;
+const BLEND_SCREEN: u32 = 4u;
+
+// Must match `OutlineWidth::unit`.
+const WIDTH_PIXELS: u32 = 0u;
+const WIDTH_LOGICAL_PIXELS: u32 = 1u;
+const WIDTH_WORLD: u32 = 2u;
+const WIDTH_SCREEN_FRACTION: u32 = 3u;
+
+// Must match `OutlinePriority::mode`.
+const PRIORITY_DEPTH_FIRST: u32 = 0u;
+const PRIORITY_FIRST: u32 = 1u;
+const PRIORITY_BLEND: u32 = 2u;
+
+// Must match `MAX_GRADIENT_STOPS`.
+const MAX_GRADIENT_STOPS: u32 = 8u;
+
+// Must match `FADE_CURVE_SAMPLES`.
+const FADE_CURVE_SAMPLES: u32 = 16u;
+
+// Must match `NO_LAYER` and `MAX_OUTLINE_LAYERS`.
+const NO_LAYER: u32 = 0xffffffffu;
+const MAX_OUTLINE_LAYERS: u32 = 8u;
+
+// Must match `OutlineStyleUniform`.
+struct OutlineStyle {
+    // rgb scaled by intensity, alpha is the outline opacity
+    color: vec4<f32>,
+    occluded_color: vec4<f32>,
+    occlusion: u32,
+    falloff: u32,
+    falloff_power: f32,
+    pattern: u32,
+    // dash length, gap and speed
+    dashes: vec3<f32>,
+    // amplitude, frequency and speed
+    noise: vec3<f32>,
+    pulse_width: vec2<f32>,
+    pulse_color: vec4<f32>,
+    pulse_period: f32,
+    placement: u32,
+    // screen offset of the band and its blur, in physical pixels
+    offset: vec2<f32>,
+    offset_blur: f32,
+    corners: u32,
+    flood_corners: u32,
+    edges: u32,
+    crease_cos: f32,
+    fill_color: vec4<f32>,
+    fill_occluded_color: vec4<f32>,
+    fill_occlusion: u32,
+    contact_color: vec4<f32>,
+    contact_width: f32,
+    contact_epsilon: f32,
+    blend_mode: u32,
+    gradient_len: u32,
+    // MAX_GRADIENT_STOPS / 4
+    gradient_offsets: array<vec4<f32>, 2>,
+    gradient_colors: array<vec4<f32>, MAX_GRADIENT_STOPS>,
+    width: f32,
+    width_unit: u32,
+    min_width: f32,
+    max_width: f32,
+    next_layer: u32,
+    // start time, starting progress and rate of the fade
+    fade: vec3<f32>,
+    // FADE_CURVE_SAMPLES / 4
+    fade_curve: array<vec4<f32>, 4>,
+}
+
+// Must match `OutlineViewUniform`.
+struct OutlineView {
+    scale_factor: f32,
+    viewport_height: f32,
+    width_scale: f32,
+    max_width: f32,
+    resolution_scale: f32,
+    texture_size: vec2<f32>,
+    overlap_candidates: u32,
+    priority_mode: u32,
+    depth_weight: f32,
+    antialias: u32,
+    time: f32,
+    // Linear color of the scene edges, transparent without them.
+    scene_edge_color: vec4<f32>,
+    scene_edge_width: f32,
+    scene_edge_depth_threshold: f32,
+    scene_edge_normal_cos: f32,
+}
+
+// Must match `EDGE_SOFTNESS`.
+const EDGE_SOFTNESS: f32 = 1.0;
+
+// Width in pixels over which the outer edge of outline bands fades out.
+fn edge_softness(view: OutlineView) -> f32 {
+    return select(0.0, EDGE_SOFTNESS, view.antialias != 0u);
+}
+
+// How far through its pulse the style is, from 0.0 at the start of each
+// period to 1.0 halfway through and back. Must match `OutlinePulse::phase`.
+fn pulse_phase(style: OutlineStyle, view: OutlineView) -> f32 {
+    return 0.5 - 0.5 * cos(6.2831853 * view.time / style.pulse_period);
+}
+
+// How visible the style is through its fade, from 0.0 (hidden) to 1.0. The
+// fade's progress runs from its start at its rate and is eased by the sampled
+// curve, which is all ones for styles that aren't fading.
+fn fade_visibility(style: OutlineStyle, view: OutlineView) -> f32 {
+    let progress = clamp(style.fade.y + (view.time - style.fade.x) * style.fade.z, 0.0, 1.0);
+    let x = progress * f32(FADE_CURVE_SAMPLES - 1u);
+    let i = min(u32(x), FADE_CURVE_SAMPLES - 2u);
+    // Arrays are only indexed at runtime through a variable.
+    var curve = style.fade_curve;
+    let a = curve[i / 4u][i % 4u];
+    let b = curve[(i + 1u) / 4u][(i + 1u) % 4u];
+    return mix(a, b, x - f32(i));
+}
+
+// Width of the band of an outline layer in pixels of the flood textures.
+// `world_scale` is the pixels per world unit the mask pass stored for the mesh.
+fn resolve_width(style: OutlineStyle, view: OutlineView, world_scale: f32) -> f32 {
+    var width = style.width;
+    if style.pulse_period > 0.0 {
+        width = mix(style.pulse_width.x, style.pulse_width.y, pulse_phase(style, view));
+    }
+    switch style.width_unit {
+        case WIDTH_LOGICAL_PIXELS: {
+            width *= view.scale_factor;
+        }
+        case WIDTH_WORLD: {
+            width *= world_scale;
+        }
+        case WIDTH_SCREEN_FRACTION: {
+            width *= view.viewport_height;
+        }
+        default: {}
+    }
+    width = clamp(width, style.min_width, style.max_width) * fade_visibility(style, view);
+    // Camera settings, then convert to pixels of the flood textures.
+    width = min(width * view.width_scale, view.max_width) * view.resolution_scale;
+    // Centered outlines split their width across both sides.
+    if style.placement == PLACEMENT_CENTERED {
+        width *= 0.5;
+    }
+    // No band needs to reach further than across the whole texture.
+    return min(width, max(view.texture_size.x, view.texture_size.y));
+}
+
+// Distance in pixels between `a` and `b`, measured so that an outline band of
+// constant distance has the given corners. Must match `OutlineCorners::distance`.
+fn corner_distance(corners: u32, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let delta = abs(a - b);
+    switch corners {
+        case CORNERS_SQUARE: {
+            return max(delta.x, delta.y);
+        }
+        case CORNERS_DIAMOND: {
+            return delta.x + delta.y;
+        }
+        default: {
+            return length(delta);
+        }
+    }
+}
+
+// Width on each side of an interior line of the style, whose band is
+// `width` wide. Interior lines are centered on their edge like centered
+// outlines, whose width is already split.
+fn interior_width(style: OutlineStyle, width: f32) -> f32 {
+    return select(width * 0.5, width, style.placement == PLACEMENT_CENTERED);
+}
+
+// Whether the mask `pixel` lies on one of the interior `edges` (EDGES_* flags)
+// of its silhouette: a crease, where its normal turns from a neighbor's by
+// more than the angle whose cosine is `crease_cos`, or the near side of a
+// depth discontinuity. `edge_texture` holds the world normals and view depths
+// of the mask, `appearance_texture` its silhouette ids.
+fn is_interior_edge(
+    edge_texture: texture_2d<f32>,
+    appearance_texture: texture_2d<f32>,
+    pixel: vec2<i32>,
+    edges: u32,
+    crease_cos: f32,
+) -> bool {
+    if (edges & EDGES_INTERIOR) == 0u {
+        return false;
+    }
+    let center = textureLoad(edge_texture, pixel, 0);
+    if center.w <= 0.0 {
+        return false;
+    }
+    let silhouette = textureLoad(appearance_texture, pixel, 0).z;
+    let last = vec2<i32>(textureDimensions(edge_texture)) - 1;
+    let offsets = array<vec2<i32>, 4>(
+        vec2<i32>(1, 0),
+        vec2<i32>(-1, 0),
+        vec2<i32>(0, 1),
+        vec2<i32>(0, -1),
+    );
+    for (var i = 0; i < 4; i++) {
+        let neighbor_pixel = clamp(pixel + offsets[i], vec2<i32>(0), last);
+        // Must match `OutlineEdges::separates`.
+        let neighbor = textureLoad(edge_texture, neighbor_pixel, 0);
+        if neighbor.w <= 0.0 || textureLoad(appearance_texture, neighbor_pixel, 0).z != silhouette {
+            continue;
+        }
+        // Meshes without normals leave them zero.
+        let has_normals = dot(center.xyz, center.xyz) > 0.5 && dot(neighbor.xyz, neighbor.xyz) > 0.5;
+        if (edges & EDGES_CREASES) != 0u && has_normals && dot(center.xyz, neighbor.xyz) < crease_cos {
+            return true;
+        }
+        if (edges & EDGES_DEPTH) != 0u && neighbor.w - center.w > DEPTH_DISCONTINUITY * center.w {
+            return true;
+        }
+    }
+    return false;
+}
@@ -7,7 +7,7 @@ use bevy_render::{
 };
 use wgpu_types::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
 
-use super::OutlineCamera;
+use super::{OutlineCamera, scene_edges::SCENE_EDGE_FORMAT, view::ExtractedOutlineView};
 
 #[derive(Clone, Component)]
 pub struct FloodTextures {
@@ -22,6 +22,15 @@ pub struct FloodTextures {
     pub outline_flood_data: CachedTexture,
     /// Stores outline color and mesh data
     pub appearance_texture: CachedTexture,
+    /// Stores the world normals and view depths of the outlined meshes, for
+    /// finding their interior edges
+    pub edge_texture: CachedTexture,
+    /// Coverage of the scene edges, when the view draws them
+    pub scene_edge_texture: Option<CachedTexture>,
+    /// Input-output of the runner-up seeds, when the view keeps two
+    /// overlapping outlines per pixel.
+    pub secondary_input: Option<CachedTexture>,
+    pub secondary_output: Option<CachedTexture>,
 }
 
 impl FloodTextures {
@@ -33,6 +42,27 @@ impl FloodTextures {
         if self.flip { &self.input } else { &self.output }
     }
 
+    pub fn secondary_input(&self) -> Option<&CachedTexture> {
+        if self.flip {
+            self.secondary_output.as_ref()
+        } else {
+            self.secondary_input.as_ref()
+        }
+    }
+
+    pub fn secondary_output(&self) -> Option<&CachedTexture> {
+        if self.flip {
+            self.secondary_input.as_ref()
+        } else {
+            self.secondary_output.as_ref()
+        }
+    }
+
+    /// Whether the flood keeps runner-up seeds.
+    pub fn is_layered(&self) -> bool {
+        self.secondary_input.is_some()
+    }
+
     pub fn flip(&mut self) {
         self.flip = !self.flip;
     }
@@ -42,12 +72,16 @@ pub fn prepare_flood_textures(
     mut commands: Commands,
     mut texture_cache: ResMut<TextureCache>,
     render_device: Res<RenderDevice>,
-    cameras: Query<(Entity, &ExtractedCamera), With<OutlineCamera>>,
+    cameras: Query<(Entity, &ExtractedCamera, &ExtractedOutlineView), With<OutlineCamera>>,
 ) {
-    for (entity, camera) in cameras.iter() {
+    for (entity, camera, outline_view) in cameras.iter() {
+        if !outline_view.enabled {
+            continue;
+        }
         let Some(target_size) = camera.physical_target_size else {
             continue;
         };
+        let target_size = outline_view.texture_size(target_size);
 
         let size = Extent3d {
             width: target_size.x,
@@ -90,13 +124,34 @@ pub fn prepare_flood_textures(
             view_formats: &[],
         };
 
+        let layered = outline_view.overlap_candidates > 1;
+        let mut secondary_texture =
+            || layered.then(|| texture_cache.get(&render_device, texture_descriptor.clone()));
+        let secondary_input = secondary_texture();
+        let secondary_output = secondary_texture();
+
+        let scene_edge_texture = outline_view.scene_edges.is_some().then(|| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("outline scene edge texture"),
+                    format: SCENE_EDGE_FORMAT,
+                    ..texture_descriptor.clone()
+                },
+            )
+        });
+
         commands.entity(entity).insert(FloodTextures {
             flip: false,
             input: texture_cache.get(&render_device, texture_descriptor.clone()),
             output: texture_cache.get(&render_device, texture_descriptor.clone()),
             outline_depth_texture: depth_texture,
             outline_flood_data: texture_cache.get(&render_device, color_storage_texture_descriptor),
-            appearance_texture: texture_cache.get(&render_device, texture_descriptor),
+            appearance_texture: texture_cache.get(&render_device, texture_descriptor.clone()),
+            edge_texture: texture_cache.get(&render_device, texture_descriptor),
+            secondary_input,
+            secondary_output,
+            scene_edge_texture,
         });
         texture_cache.update();
     }
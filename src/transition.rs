@@ -0,0 +1,246 @@
+use bevy::{math::curve::EaseFunction, prelude::*};
+
+use super::{FadingOutline, MeshOutline, MeshOutlines, OutlineFade, OutlineTransition};
+
+/// Starts fading in outlines inserted on entities with an
+/// [`OutlineTransition`], or resumes from a fade-out cut short by the new
+/// outline.
+///
+/// Replacing an outline that is already shown doesn't fade it in again.
+pub(crate) fn start_fade_in(
+    insert: On<Insert, (MeshOutline, MeshOutlines)>,
+    mut commands: Commands,
+    entities: Query<(Has<OutlineTransition>, Has<OutlineFade>, Has<FadingOutline>)>,
+) {
+    let entity = insert.entity;
+    match entities.get(entity) {
+        // Keep the visibility reached so far and fade in from there.
+        Ok((_, _, true)) => {
+            commands.entity(entity).remove::<FadingOutline>();
+        }
+        Ok((true, false, false)) => {
+            commands.entity(entity).insert(OutlineFade::default());
+        }
+        _ => {}
+    }
+}
+
+/// Keeps a removed [`MeshOutline`] fading out, unless a [`MeshOutlines`] on
+/// the same entity takes over.
+pub(crate) fn start_fade_out(
+    remove: On<Remove, MeshOutline>,
+    mut commands: Commands,
+    outlines: Query<
+        (&MeshOutline, &OutlineTransition, Option<&OutlineFade>),
+        Without<MeshOutlines>,
+    >,
+) {
+    let entity = remove.entity;
+    let Ok((outline, transition, fade)) = outlines.get(entity) else {
+        return;
+    };
+    fade_out(
+        &mut commands,
+        entity,
+        vec![outline.clone()],
+        transition,
+        fade,
+    );
+}
+
+/// Keeps a removed [`MeshOutlines`] stack fading out, unless a
+/// [`MeshOutline`] on the same entity takes over.
+pub(crate) fn start_stack_fade_out(
+    remove: On<Remove, MeshOutlines>,
+    mut commands: Commands,
+    outlines: Query<
+        (&MeshOutlines, &OutlineTransition, Option<&OutlineFade>),
+        Without<MeshOutline>,
+    >,
+) {
+    let entity = remove.entity;
+    let Ok((outlines, transition, fade)) = outlines.get(entity) else {
+        return;
+    };
+    fade_out(&mut commands, entity, outlines.0.clone(), transition, fade);
+}
+
+fn fade_out(
+    commands: &mut Commands,
+    entity: Entity,
+    layers: Vec<MeshOutline>,
+    transition: &OutlineTransition,
+    fade: Option<&OutlineFade>,
+) {
+    // Despawned entities are gone by the time the commands run, which
+    // `try_insert` ignores.
+    commands.entity(entity).try_insert((
+        FadingOutline {
+            layers,
+            transition: *transition,
+        },
+        fade.copied().unwrap_or(OutlineFade::VISIBLE),
+    ));
+}
+
+/// Advances fading outlines, and drops fading-out ones once they are fully
+/// hidden.
+///
+/// Each run of a fade in one direction is recorded in its [`OutlineFade`]
+/// when it starts, so the GPU can animate the outline without its appearance
+/// changing every frame.
+pub(crate) fn advance_outline_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fades: Query<(
+        Entity,
+        &mut OutlineFade,
+        Option<&OutlineTransition>,
+        Option<&FadingOutline>,
+    )>,
+) {
+    let rate = |duration: f32| {
+        if duration > 0.0 {
+            duration.recip()
+        } else {
+            f32::INFINITY
+        }
+    };
+    let now = time.elapsed_secs_wrapped();
+    for (entity, mut fade, transition, fading) in fades.iter_mut() {
+        let (rate, easing) = match (fading, transition) {
+            (Some(fading), _) => (-rate(fading.transition.fade_out), fading.transition.easing),
+            (None, Some(transition)) if fade.progress < 1.0 => {
+                (rate(transition.fade_in), transition.easing)
+            }
+            // Without a transition anymore, show the outline right away.
+            (None, None) if fade.progress < 1.0 => (f32::INFINITY, EaseFunction::Linear),
+            _ => {
+                if *fade != OutlineFade::VISIBLE {
+                    *fade = OutlineFade::VISIBLE;
+                }
+                continue;
+            }
+        };
+
+        // A new run starts from the previous frame, so it advances right away.
+        // Runs also restart when the wrapped time wraps around.
+        if fade.rate != rate || fade.easing != easing || now < fade.start_time {
+            fade.start_time = now - time.delta_secs();
+            fade.start_progress = fade.progress;
+            fade.rate = rate;
+            fade.easing = easing;
+        }
+        let progress = if rate.is_finite() {
+            fade.start_progress + (now - fade.start_time) * rate
+        } else {
+            rate.signum()
+        };
+
+        if progress <= 0.0 && fading.is_some() {
+            commands
+                .entity(entity)
+                .remove::<(FadingOutline, OutlineFade)>();
+        } else if progress >= 1.0 && fading.is_none() {
+            *fade = OutlineFade::VISIBLE;
+        } else {
+            fade.progress = progress.clamp(0.0, 1.0);
+            fade.visibility = easing.sample_clamped(fade.progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use crate::{FadingOutline, MeshOutline, OutlineFade, OutlineTransition};
+
+    use super::{advance_outline_transitions, start_fade_in, start_fade_out};
+
+    /// Outlines fade in on insertion, keep fading out after removal, pick up
+    /// where they were when inserted again mid-fade, and are dropped once
+    /// hidden.
+    #[test]
+    fn outlines_fade_in_and_out() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_observer(start_fade_in)
+            .add_observer(start_fade_out)
+            .add_systems(Update, advance_outline_transitions);
+        let step = |app: &mut App| {
+            let mut time = app.world_mut().resource_mut::<Time>();
+            time.advance_by(Duration::from_millis(250));
+            app.update();
+        };
+        let progress = |app: &App, entity: Entity| {
+            app.world()
+                .get::<OutlineFade>(entity)
+                .map(|fade| fade.progress)
+        };
+
+        let entity = app
+            .world_mut()
+            .spawn((MeshOutline::new(4.0), OutlineTransition::new(0.5, 1.0)))
+            .id();
+        assert_eq!(progress(&app, entity), Some(0.0));
+        step(&mut app);
+        assert_eq!(progress(&app, entity), Some(0.5));
+
+        app.world_mut().entity_mut(entity).remove::<MeshOutline>();
+        step(&mut app);
+        assert_eq!(progress(&app, entity), Some(0.25));
+        assert!(app.world().get::<FadingOutline>(entity).is_some());
+
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(MeshOutline::new(4.0));
+        step(&mut app);
+        assert_eq!(progress(&app, entity), Some(0.75));
+        assert!(app.world().get::<FadingOutline>(entity).is_none());
+
+        app.world_mut().entity_mut(entity).remove::<MeshOutline>();
+        for _ in 0..3 {
+            step(&mut app);
+        }
+        assert_eq!(progress(&app, entity), None);
+        assert!(app.world().get::<FadingOutline>(entity).is_none());
+    }
+
+    /// The parameters the GPU animates a fade with only change when the fade
+    /// turns around, so the outline's appearance stays the same meanwhile.
+    #[test]
+    fn fades_keep_their_gpu_params_while_running() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_observer(start_fade_in)
+            .add_observer(start_fade_out)
+            .add_systems(Update, advance_outline_transitions);
+        let step = |app: &mut App| {
+            let mut time = app.world_mut().resource_mut::<Time>();
+            time.advance_by(Duration::from_millis(100));
+            app.update();
+        };
+        let params = |app: &App, entity: Entity| {
+            OutlineFade::gpu_params(app.world().get::<OutlineFade>(entity))
+        };
+
+        let entity = app
+            .world_mut()
+            .spawn((MeshOutline::new(4.0), OutlineTransition::new(1.0, 1.0)))
+            .id();
+        step(&mut app);
+        let fading_in = params(&app, entity);
+        step(&mut app);
+        assert_eq!(params(&app, entity), fading_in);
+
+        app.world_mut().entity_mut(entity).remove::<MeshOutline>();
+        step(&mut app);
+        let fading_out = params(&app, entity);
+        assert_ne!(fading_out, fading_in);
+        step(&mut app);
+        assert_eq!(params(&app, entity), fading_out);
+    }
+}
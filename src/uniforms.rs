@@ -5,26 +5,278 @@ use bevy::render::render_resource::ShaderType;
 use bevy_render::render_resource::AsBindGroup;
 use bytemuck::{Pod, Zeroable};
 
-use super::ExtractedOutline;
+use super::{ExtractedOutline, FADE_CURVE_SAMPLES, MAX_GRADIENT_STOPS, OutlineCorners};
 
+/// Per-appearance uniform bound while drawing the mask pass.
 #[derive(Debug, Clone, AsBindGroup, ShaderType, Pod, Zeroable, Copy)]
 #[repr(C)]
 pub struct OutlineUniform {
-    pub intensity: f32,
-    pub width: f32,
+    /// [`crate::MeshOutline::priority`], as the float the mask pass writes.
     pub priority: f32,
-    pub _padding: f32,
-    pub outline_color: Vec4,
+    /// Slot of this appearance in the [`crate::render::OutlineStyles`] buffer.
+    pub style_index: u32,
+    /// [`crate::OutlineGroup`] plus one, or zero outside of any group.
+    pub group: u32,
+    /// The entity's rank in [`crate::OutlineTieBreaks`], which identifies its
+    /// silhouette and settles ties with other outlines.
+    pub tie_break: u32,
 }
 
-impl From<&ExtractedOutline> for OutlineUniform {
-    fn from(outline: &ExtractedOutline) -> Self {
+impl OutlineUniform {
+    pub fn new(outline: &ExtractedOutline, style_index: u32, tie_break: u32) -> Self {
         OutlineUniform {
-            intensity: outline.intensity,
-            width: outline.width,
-            priority: outline.priority,
-            _padding: 0.0,
-            outline_color: outline.color,
+            priority: outline.priority as f32,
+            style_index,
+            group: outline.group.map_or(0, |group| group + 1),
+            tie_break,
         }
     }
 }
+
+/// `next_layer` of the last layer of an outline.
+pub const NO_LAYER: u32 = u32::MAX;
+
+/// Per-appearance data read by the flood and compose passes, indexed by
+/// [`OutlineUniform::style_index`].
+#[derive(Debug, Clone, Default, ShaderType)]
+pub struct OutlineStyleUniform {
+    pub color: Vec4,
+    pub occluded_color: Vec4,
+    pub occlusion: u32,
+    pub falloff: u32,
+    pub falloff_power: f32,
+    pub pattern: u32,
+    /// Dash length, gap and speed, in physical pixels.
+    pub dashes: Vec3,
+    /// Noise amplitude in physical pixels, frequency and speed.
+    pub noise: Vec3,
+    /// Smallest and largest width of the pulse, in the unit of `width`.
+    pub pulse_width: Vec2,
+    /// Color halfway through the pulse, scaled by intensity like `color`.
+    pub pulse_color: Vec4,
+    /// Seconds per pulse, zero without one.
+    pub pulse_period: f32,
+    pub placement: u32,
+    /// Screen offset of the band and the width its edge fades out over, in
+    /// physical pixels.
+    pub offset: Vec2,
+    pub offset_blur: f32,
+    pub corners: u32,
+    /// Corners the flood measures seeds of this style with; see
+    /// [`flood_corners`].
+    pub flood_corners: u32,
+    /// [`crate::OutlineEdges`] flags.
+    pub edges: u32,
+    /// Cosine of the crease angle.
+    pub crease_cos: f32,
+    pub fill_color: Vec4,
+    pub fill_occluded_color: Vec4,
+    pub fill_occlusion: u32,
+    /// Contact line color, transparent without one.
+    pub contact_color: Vec4,
+    /// Contact line width, in physical pixels.
+    pub contact_width: f32,
+    pub contact_epsilon: f32,
+    pub blend_mode: u32,
+    /// Number of gradient stops, zero to draw the band in `color`.
+    pub gradient_len: u32,
+    /// Offsets of the gradient stops, four per vector.
+    pub gradient_offsets: [Vec4; MAX_GRADIENT_STOPS / 4],
+    /// Colors of the gradient stops, scaled by intensity like `color`.
+    pub gradient_colors: [Vec4; MAX_GRADIENT_STOPS],
+    /// Width in the unit given by `width_unit`, resolved to pixels by the
+    /// flood and compose passes.
+    pub width: f32,
+    pub width_unit: u32,
+    pub min_width: f32,
+    pub max_width: f32,
+    /// Style slot of the layer stacked over this one, or [`NO_LAYER`].
+    pub next_layer: u32,
+    /// Start time, starting progress and rate of the outline's fade.
+    pub fade: Vec3,
+    /// Samples of the fade's easing curve, four per vector.
+    pub fade_curve: [Vec4; FADE_CURVE_SAMPLES / 4],
+}
+
+impl OutlineStyleUniform {
+    pub fn new(outline: &ExtractedOutline, next_layer: u32) -> Self {
+        // Intensity fades the outline up to 1.0 and brightens it beyond.
+        let apply_intensity = |color: Vec4| {
+            (color.truncate() * outline.intensity.max(1.0))
+                .extend(color.w * outline.intensity.clamp(0.0, 1.0))
+        };
+        let mut gradient_offsets = [Vec4::ZERO; MAX_GRADIENT_STOPS / 4];
+        let mut gradient_colors = [Vec4::ZERO; MAX_GRADIENT_STOPS];
+        for (i, &(offset, color)) in outline.gradient.iter().enumerate() {
+            gradient_offsets[i / 4][i % 4] = offset;
+            gradient_colors[i] = apply_intensity(color);
+        }
+
+        OutlineStyleUniform {
+            color: apply_intensity(outline.color),
+            occluded_color: outline.occluded_color,
+            occlusion: outline.occlusion,
+            falloff: outline.falloff,
+            falloff_power: outline.falloff_power,
+            pattern: outline.pattern,
+            dashes: outline.dashes,
+            noise: outline.noise,
+            pulse_width: outline.pulse_width,
+            pulse_color: apply_intensity(outline.pulse_color),
+            pulse_period: outline.pulse_period,
+            placement: outline.placement,
+            offset: outline.offset,
+            offset_blur: outline.offset_blur,
+            corners: outline.corners,
+            flood_corners: flood_corners(outline),
+            edges: outline.edges,
+            crease_cos: outline.crease_angle.cos(),
+            fill_color: outline.fill_color,
+            fill_occluded_color: outline.fill_occluded_color,
+            fill_occlusion: outline.fill_occlusion,
+            contact_color: outline.contact_color,
+            contact_width: outline.contact_width,
+            contact_epsilon: outline.contact_epsilon,
+            blend_mode: outline.blend_mode,
+            gradient_len: outline.gradient.len() as u32,
+            gradient_offsets,
+            gradient_colors,
+            width: outline.width.value(),
+            width_unit: outline.width.unit(),
+            min_width: outline.min_width,
+            max_width: outline.max_width,
+            next_layer,
+            fade: outline.fade,
+            fade_curve: std::array::from_fn(|i| Vec4::from_slice(&outline.fade_curve[i * 4..])),
+        }
+    }
+}
+
+/// The corners of `outline`'s layers that measure the shortest distances
+/// (Chebyshev, then Euclidean, then Manhattan), so the flood carries the seeds
+/// of a stack as far as the band of any of its layers reaches.
+fn flood_corners(outline: &ExtractedOutline) -> u32 {
+    let reach = [
+        OutlineCorners::Square,
+        OutlineCorners::Round,
+        OutlineCorners::Diamond,
+    ]
+    .map(|corners| corners.mode());
+    outline
+        .iter_layers()
+        .map(|layer| layer.corners)
+        .min_by_key(|corners| reach.iter().position(|mode| mode == corners))
+        .unwrap_or(outline.corners)
+}
+
+/// Per-view data for the flood and compose passes, indexed by [`crate::view::OutlineViewUniformOffset`].
+#[derive(Debug, Clone, Default, ShaderType)]
+pub struct OutlineViewUniform {
+    /// Scale factor of the view's render target, for logical pixel widths.
+    pub scale_factor: f32,
+    /// Viewport height in physical pixels, for screen fraction widths.
+    pub viewport_height: f32,
+    /// See [`crate::OutlineCameraSettings::width_scale`].
+    pub width_scale: f32,
+    /// Cap of the scaled width in physical pixels, `f32::MAX` without one.
+    pub max_width: f32,
+    /// Size of the outline textures relative to the render target.
+    pub resolution_scale: f32,
+    /// Size of the outline textures in pixels, which turns the mask pass's
+    /// fragment coordinates into texture uvs.
+    pub texture_size: Vec2,
+    /// Overlapping outlines kept per pixel, 1 or 2.
+    pub overlap_candidates: u32,
+    /// See [`crate::OutlinePriority::mode`].
+    pub priority_mode: u32,
+    /// See [`crate::OutlinePriority::Blend`].
+    pub depth_weight: f32,
+    /// See [`crate::OutlineCameraSettings::antialias`].
+    pub antialias: u32,
+    /// Seconds since startup, wrapped to keep precision, for animated
+    /// patterns.
+    pub time: f32,
+    /// Linear color of the [`crate::OutlineSceneEdges`], transparent without
+    /// them.
+    pub scene_edge_color: Vec4,
+    pub scene_edge_width: f32,
+    pub scene_edge_depth_threshold: f32,
+    /// Cosine of the normal threshold.
+    pub scene_edge_normal_cos: f32,
+}
+
+/// Width in pixels of the smoothed outer edge of anti-aliased outline bands.
+pub const EDGE_SOFTNESS: f32 = 1.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_OUTLINE_GROUP;
+
+    /// Intensity fades the opacity below 1.0 and brightens the color above.
+    #[test]
+    fn intensity_scales_opacity_then_brightness() {
+        let color = Vec4::new(0.5, 0.25, 1.0, 0.8);
+        let style = |intensity| {
+            OutlineStyleUniform::new(
+                &ExtractedOutline {
+                    intensity,
+                    color,
+                    ..Default::default()
+                },
+                NO_LAYER,
+            )
+        };
+
+        assert_eq!(style(0.5).color, Vec4::new(0.5, 0.25, 1.0, 0.4));
+        assert_eq!(style(1.0).color, color);
+        assert_eq!(style(2.0).color, Vec4::new(1.0, 0.5, 2.0, 0.8));
+    }
+
+    /// Zero is left for ungrouped outlines, so group ids are shifted by one.
+    #[test]
+    fn groups_are_encoded_past_zero() {
+        let group = |group| {
+            OutlineUniform::new(
+                &ExtractedOutline {
+                    group,
+                    ..Default::default()
+                },
+                0,
+                0,
+            )
+            .group
+        };
+        assert_eq!(group(None), 0);
+        assert_eq!(group(Some(0)), 1);
+        assert_eq!(group(Some(7)), 8);
+
+        // The largest group still converts to a float the GPU tells apart
+        // from its neighbor.
+        let largest = group(Some(MAX_OUTLINE_GROUP));
+        assert_eq!(largest as f32 as u32, largest);
+        assert_ne!(largest as f32, (largest - 1) as f32);
+    }
+
+    /// Gradient offsets are packed four to a vector, and colors scaled by
+    /// intensity like the outline color.
+    #[test]
+    fn gradients_are_packed_for_the_gpu() {
+        let gradient: Vec<_> = (0..6)
+            .map(|i| (i as f32 / 5.0, Vec4::new(i as f32, 0.0, 0.0, 1.0)))
+            .collect();
+        let style = OutlineStyleUniform::new(
+            &ExtractedOutline {
+                intensity: 0.5,
+                gradient,
+                ..Default::default()
+            },
+            NO_LAYER,
+        );
+        assert_eq!(style.gradient_len, 6);
+        assert_eq!(style.gradient_offsets[0], Vec4::new(0.0, 0.2, 0.4, 0.6));
+        assert_eq!(style.gradient_offsets[1], Vec4::new(0.8, 1.0, 0.0, 0.0));
+        assert_eq!(style.gradient_colors[5], Vec4::new(5.0, 0.0, 0.0, 0.5));
+        assert_eq!(style.gradient_colors[6], Vec4::ZERO);
+    }
+}
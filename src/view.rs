@@ -1,12 +1,26 @@
-use bevy::{platform::collections::HashSet, prelude::*};
+use bevy::{
+    camera::{Viewport, visibility::RenderLayers},
+    platform::collections::HashSet,
+    prelude::*,
+};
 use bevy_render::{
     Extract,
     batching::gpu_preprocessing::{GpuPreprocessingMode, GpuPreprocessingSupport},
+    camera::ExtractedCamera,
     render_phase::ViewBinnedRenderPhases,
-    view::{NoIndirectDrawing, RetainedViewEntity},
+    render_resource::DynamicUniformBuffer,
+    renderer::{RenderDevice, RenderQueue},
+    sync_world::RenderEntity,
+    view::{ExtractedView, NoIndirectDrawing, RetainedViewEntity},
 };
 
-use super::mask::MeshOutline3d;
+use super::{
+    ExtractedOutline, OutlineCamera, OutlineCameraSettings, OutlineCategories, OutlineLayers,
+    OutlinePriority, OutlineSceneEdges, clamp_overlap_candidates,
+    mask::MeshOutline3d,
+    uniforms::{EDGE_SOFTNESS, OutlineViewUniform},
+    visibility::camera_outline_layers,
+};
 
 #[allow(clippy::type_complexity)]
 pub(crate) fn update_views(
@@ -44,3 +58,247 @@ pub(crate) fn update_views(
     }
     outline_phases.retain(|view_entity, _| live_entities.contains(view_entity));
 }
+
+/// Outline-related state of an [`OutlineCamera`], extracted from the main world.
+#[derive(Component, Clone)]
+pub struct ExtractedOutlineView {
+    pub scale_factor: f32,
+    pub enabled: bool,
+    pub width_scale: f32,
+    /// `f32::MAX` when the camera doesn't cap outline widths.
+    pub max_width: f32,
+    pub resolution_scale: f32,
+    pub categories: OutlineCategories,
+    /// The camera's [`OutlineLayers`], or its [`RenderLayers`] without one.
+    pub layers: RenderLayers,
+    /// Clamped to `1..=MAX_OVERLAP_CANDIDATES`.
+    pub overlap_candidates: u32,
+    pub priority: OutlinePriority,
+    pub antialias: bool,
+    pub scene_edges: Option<OutlineSceneEdges>,
+}
+
+/// A view with the default [`OutlineCameraSettings`], for tests to override
+/// fields of.
+#[cfg(test)]
+impl Default for ExtractedOutlineView {
+    fn default() -> Self {
+        ExtractedOutlineView {
+            scale_factor: 1.0,
+            enabled: true,
+            width_scale: 1.0,
+            max_width: f32::MAX,
+            resolution_scale: 1.0,
+            categories: OutlineCategories::ALL,
+            layers: RenderLayers::default(),
+            overlap_candidates: 1,
+            priority: OutlinePriority::default(),
+            antialias: true,
+            scene_edges: None,
+        }
+    }
+}
+
+impl ExtractedOutlineView {
+    /// Whether this view draws `outline`.
+    pub fn shows(&self, outline: &ExtractedOutline) -> bool {
+        outline.categories.intersects(self.categories)
+            && outline
+                .layers
+                .as_ref()
+                .is_none_or(|layers| layers.intersects(&self.layers))
+    }
+
+    /// Width in pixels over which the outer edge of outline bands fades out.
+    pub fn edge_softness(&self) -> f32 {
+        if self.antialias { EDGE_SOFTNESS } else { 0.0 }
+    }
+
+    /// Size of the outline textures for a render target of `target_size`.
+    pub fn texture_size(&self, target_size: UVec2) -> UVec2 {
+        (target_size.as_vec2() * self.resolution_scale)
+            .round()
+            .as_uvec2()
+            .max(UVec2::ONE)
+    }
+
+    /// The camera's `viewport` mapped onto the outline textures.
+    pub fn texture_viewport(&self, viewport: &Viewport) -> Viewport {
+        let scale = |value: UVec2| (value.as_vec2() * self.resolution_scale).round().as_uvec2();
+        let start = scale(viewport.physical_position);
+        let end = scale(viewport.physical_position + viewport.physical_size);
+        Viewport {
+            physical_position: start,
+            physical_size: (end - start).max(UVec2::ONE),
+            depth: viewport.depth.clone(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn extract_outline_views(
+    mut commands: Commands,
+    cameras: Extract<
+        Query<
+            (
+                RenderEntity,
+                &Camera,
+                Option<&OutlineCameraSettings>,
+                Option<&OutlineLayers>,
+                Option<&RenderLayers>,
+            ),
+            With<OutlineCamera>,
+        >,
+    >,
+) {
+    for (render_entity, camera, settings, outline_layers, render_layers) in cameras.iter() {
+        let Ok(mut entity) = commands.get_entity(render_entity) else {
+            continue;
+        };
+        let settings = settings.cloned().unwrap_or_default();
+        entity.insert(ExtractedOutlineView {
+            scale_factor: camera.target_scaling_factor().unwrap_or(1.0),
+            enabled: settings.enabled,
+            width_scale: settings.width_scale,
+            max_width: settings.max_width.unwrap_or(f32::MAX),
+            resolution_scale: settings.resolution_scale,
+            categories: settings.categories,
+            layers: camera_outline_layers(outline_layers, render_layers),
+            overlap_candidates: clamp_overlap_candidates(settings.overlap_candidates),
+            priority: settings.priority,
+            antialias: settings.antialias,
+            scene_edges: settings.scene_edges,
+        });
+    }
+}
+
+/// One [`OutlineViewUniform`] per outline camera, read by the mask, flood and
+/// compose passes.
+#[derive(Resource, Default)]
+pub struct OutlineViewUniforms {
+    pub uniforms: DynamicUniformBuffer<OutlineViewUniform>,
+}
+
+/// Offset of a view's entry in [`OutlineViewUniforms`].
+#[derive(Component)]
+pub struct OutlineViewUniformOffset {
+    pub offset: u32,
+}
+
+pub fn prepare_outline_view_uniforms(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut view_uniforms: ResMut<OutlineViewUniforms>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &ExtractedCamera,
+            &ExtractedOutlineView,
+        ),
+        With<OutlineCamera>,
+    >,
+    time: Res<Time>,
+) {
+    let Some(mut writer) =
+        view_uniforms
+            .uniforms
+            .get_writer(views.iter().len(), &render_device, &render_queue)
+    else {
+        return;
+    };
+
+    for (entity, extracted_view, camera, view) in views.iter() {
+        let texture_size = view.texture_size(camera.physical_target_size.unwrap_or_default());
+        let scene_edges = view.scene_edges.unwrap_or_default();
+        let offset = writer.write(&OutlineViewUniform {
+            scale_factor: view.scale_factor,
+            viewport_height: extracted_view.viewport.w as f32,
+            width_scale: view.width_scale,
+            max_width: view.max_width,
+            resolution_scale: view.resolution_scale,
+            texture_size: texture_size.as_vec2(),
+            overlap_candidates: view.overlap_candidates,
+            priority_mode: view.priority.mode(),
+            depth_weight: view.priority.depth_weight(),
+            antialias: view.antialias.into(),
+            time: time.elapsed_secs_wrapped(),
+            scene_edge_color: view.scene_edges.map_or(Vec4::ZERO, |scene_edges| {
+                LinearRgba::from(scene_edges.color).to_vec4()
+            }),
+            // The prepass textures the edges are found in are full resolution.
+            scene_edge_width: scene_edges.width,
+            scene_edge_depth_threshold: scene_edges.depth_threshold,
+            scene_edge_normal_cos: scene_edges.normal_threshold.cos(),
+        });
+        commands
+            .entity(entity)
+            .insert(OutlineViewUniformOffset { offset });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(resolution_scale: f32) -> ExtractedOutlineView {
+        ExtractedOutlineView {
+            resolution_scale,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn outline_textures_follow_the_resolution_scale() {
+        let half = view(0.5);
+        assert_eq!(
+            half.texture_size(UVec2::new(1920, 1081)),
+            UVec2::new(960, 541)
+        );
+        assert_eq!(view(0.0001).texture_size(UVec2::new(100, 100)), UVec2::ONE);
+
+        let viewport = half.texture_viewport(&Viewport {
+            physical_position: UVec2::new(960, 0),
+            physical_size: UVec2::new(960, 1080),
+            ..default()
+        });
+        assert_eq!(viewport.physical_position, UVec2::new(480, 0));
+        assert_eq!(viewport.physical_size, UVec2::new(480, 540));
+    }
+
+    /// Outlines with [`OutlineLayers`] go by the view's outline layers, and
+    /// all outlines by its categories.
+    #[test]
+    fn views_show_outlines_on_their_layers_and_categories() {
+        let view = ExtractedOutlineView {
+            layers: RenderLayers::layer(1),
+            categories: OutlineCategories::category(2),
+            ..view(1.0)
+        };
+        let outline = |layers: Option<RenderLayers>, categories| ExtractedOutline {
+            layers,
+            categories,
+            ..Default::default()
+        };
+        let in_category = OutlineCategories::category(2);
+
+        assert!(view.shows(&outline(None, in_category)));
+        assert!(view.shows(&outline(
+            Some(RenderLayers::from_layers(&[0, 1])),
+            in_category
+        )));
+        assert!(!view.shows(&outline(Some(RenderLayers::layer(0)), in_category)));
+        assert!(!view.shows(&outline(None, OutlineCategories::category(0))));
+    }
+
+    #[test]
+    fn only_antialiased_views_soften_band_edges() {
+        assert_eq!(view(1.0).edge_softness(), EDGE_SOFTNESS);
+        let aliased = ExtractedOutlineView {
+            antialias: false,
+            ..view(1.0)
+        };
+        assert_eq!(aliased.edge_softness(), 0.0);
+    }
+}
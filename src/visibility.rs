@@ -0,0 +1,152 @@
+use core::any::TypeId;
+
+use bevy::{
+    camera::{
+        primitives::{Aabb, Frustum},
+        visibility::{NoFrustumCulling, RenderLayers, SetViewVisibility, VisibleEntities},
+    },
+    prelude::*,
+};
+
+use super::{OutlineCamera, OutlineLayers};
+
+/// The outline layers of a camera: its [`OutlineLayers`], or its
+/// [`RenderLayers`] without one.
+pub(crate) fn camera_outline_layers(
+    outline_layers: Option<&OutlineLayers>,
+    render_layers: Option<&RenderLayers>,
+) -> RenderLayers {
+    outline_layers
+        .map(|layers| layers.0.clone())
+        .or_else(|| render_layers.cloned())
+        .unwrap_or_default()
+}
+
+/// Lists meshes with [`OutlineLayers`] as visible to the outline cameras
+/// whose outline layers they intersect, under their own visibility class,
+/// whether or not the camera sees the mesh itself. They are frustum culled by
+/// their `Aabb` like other meshes, and marked visible so they are extracted.
+///
+/// `queue_outline` only draws the outlines of these meshes from this class.
+#[allow(clippy::type_complexity)]
+pub(crate) fn check_outline_layer_visibility(
+    mut cameras: Query<
+        (
+            &Camera,
+            &Frustum,
+            &mut VisibleEntities,
+            Option<&OutlineLayers>,
+            Option<&RenderLayers>,
+        ),
+        With<OutlineCamera>,
+    >,
+    mut meshes: Query<
+        (
+            Entity,
+            &OutlineLayers,
+            &InheritedVisibility,
+            &mut ViewVisibility,
+            &GlobalTransform,
+            Option<&Aabb>,
+            Has<NoFrustumCulling>,
+        ),
+        With<Mesh3d>,
+    >,
+) {
+    let class = TypeId::of::<OutlineLayers>();
+
+    for (camera, frustum, mut visible_entities, outline_layers, render_layers) in cameras.iter_mut()
+    {
+        if !camera.is_active {
+            continue;
+        }
+        let view_layers = camera_outline_layers(outline_layers, render_layers);
+
+        let visible = visible_entities.get_mut(class);
+        for (entity, layers, inherited, mut view_visibility, transform, aabb, no_culling) in
+            meshes.iter_mut()
+        {
+            if !inherited.get() || !layers.intersects(&view_layers) {
+                continue;
+            }
+            if !no_culling
+                && let Some(aabb) = aabb
+                && !frustum.intersects_obb(aabb, &transform.affine(), true, false)
+            {
+                continue;
+            }
+            view_visibility.set_visible();
+            visible.push(entity);
+        }
+        // Visible entity lists are kept sorted.
+        visible.sort_unstable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{camera::CameraProjection, math::primitives::ViewFrustum};
+
+    use super::*;
+
+    /// A camera that renders none of the meshes still lists the ones on its
+    /// outline layers, unless they are out of view.
+    #[test]
+    fn outline_layers_are_visible_without_the_mesh() {
+        let mut app = App::new();
+        app.add_systems(Update, check_outline_layer_visibility);
+
+        // Looking down -Z from 10 units away, at the origin.
+        let clip_from_world = PerspectiveProjection::default().get_clip_from_view()
+            * Mat4::from_translation(Vec3::new(0.0, 0.0, -10.0));
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera::default(),
+                Frustum(ViewFrustum::from_clip_from_world(&clip_from_world)),
+                OutlineCamera,
+                RenderLayers::layer(2),
+                OutlineLayers(RenderLayers::layer(1)),
+            ))
+            .id();
+
+        let mut spawn_mesh = |translation: Vec3, layer: usize| {
+            app.world_mut()
+                .spawn((
+                    Mesh3d::default(),
+                    OutlineLayers(RenderLayers::layer(layer)),
+                    Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+                    GlobalTransform::from_translation(translation),
+                    InheritedVisibility::VISIBLE,
+                    ViewVisibility::default(),
+                ))
+                .id()
+        };
+        let shown = spawn_mesh(Vec3::ZERO, 1);
+        let behind = spawn_mesh(Vec3::new(0.0, 0.0, 20.0), 1);
+        let other_layer = spawn_mesh(Vec3::ZERO, 3);
+        app.update();
+
+        let world = app.world();
+        let visible = world.get::<VisibleEntities>(camera).unwrap();
+        assert_eq!(visible.get(TypeId::of::<OutlineLayers>()), &[shown]);
+        assert!(world.get::<ViewVisibility>(shown).unwrap().get());
+        assert!(!world.get::<ViewVisibility>(behind).unwrap().get());
+        assert!(!world.get::<ViewVisibility>(other_layer).unwrap().get());
+    }
+
+    #[test]
+    fn camera_outline_layers_prefer_outline_layers() {
+        let outline_layers = OutlineLayers(RenderLayers::layer(1));
+        let render_layers = RenderLayers::layer(2);
+        assert_eq!(
+            camera_outline_layers(Some(&outline_layers), Some(&render_layers)),
+            RenderLayers::layer(1)
+        );
+        assert_eq!(
+            camera_outline_layers(None, Some(&render_layers)),
+            RenderLayers::layer(2)
+        );
+        assert_eq!(camera_outline_layers(None, None), RenderLayers::default());
+    }
+}
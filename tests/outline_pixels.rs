@@ -0,0 +1,486 @@
+//! Pixel tests of what the outline passes draw.
+//!
+//! Each test renders a scene headlessly to a small off-screen image, through an
+//! orthographic `OutlineCamera` where one world unit is one pixel, and reads
+//! the image back to check the colors of chosen pixels. Meshes are unlit and
+//! tonemapping is off, so every pixel is exactly one of the scene's colors.
+//!
+//! They need a real GPU adapter, so they are `#[ignore]`d by default like
+//! `msaa_disabled`. Run them locally with:
+//!
+//! ```sh
+//! cargo test --test outline_pixels -- --ignored
+//! ```
+
+use std::{
+    f32::consts::FRAC_PI_4,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    camera::{RenderTarget, ScalingMode},
+    core_pipeline::{prepass::DepthPrepass, tonemapping::Tonemapping},
+    prelude::*,
+    render::{
+        RenderPlugin,
+        gpu_readback::{Readback, ReadbackComplete},
+        pipelined_rendering::PipelinedRenderingPlugin,
+        render_resource::TextureFormat,
+    },
+    window::{ExitCondition, WindowPlugin},
+    winit::WinitPlugin,
+};
+use bevy_mesh_outline::{
+    MeshOutline, MeshOutlinePlugin, OutlineCamera, OutlineCameraSettings, OutlineContactLine,
+    OutlineCorners, OutlineFill, OutlineGradient, OutlineOcclusion, OutlineOffset,
+    OutlinePlacement,
+};
+
+/// Width and height of the rendered image, in pixels and world units.
+const SIZE: u32 = 64;
+
+const RED: Srgba = Srgba::RED;
+const GREEN: Srgba = Srgba::GREEN;
+const BLUE: Srgba = Srgba::BLUE;
+const WHITE: Srgba = Srgba::WHITE;
+const BLACK: Srgba = Srgba::BLACK;
+
+/// The 20 unit cube most tests outline, centered in the image: it covers
+/// pixels 22 to 41 of each row and column through the middle.
+const CUBE: f32 = 20.0;
+
+/// X-ray outlines draw their band and mesh in the x-ray color where scene
+/// geometry hides them, and as usual elsewhere.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn xray_outlines_show_through_occluders() {
+    // An occluder covering the right half of the image, in front of the cube.
+    let scene = |occlusion| {
+        move |world: &mut World| {
+            spawn_cuboid(
+                world,
+                Vec3::new(32.0, 64.0, 1.0),
+                Vec3::new(16.0, 0.0, 20.0),
+                BLUE,
+            );
+            spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(
+                MeshOutline::new(6.0)
+                    .with_color(RED.into())
+                    .with_occlusion(occlusion),
+            );
+        }
+    };
+
+    let frame = render(
+        Msaa::Off,
+        scene(OutlineOcclusion::XRay {
+            color: GREEN.into(),
+        }),
+    );
+    // Left band and mesh, in plain view.
+    frame.assert_pixel(19, 32, RED);
+    frame.assert_pixel(27, 32, WHITE);
+    // Right mesh and band, behind the occluder.
+    frame.assert_pixel(37, 32, GREEN);
+    frame.assert_pixel(45, 32, GREEN);
+    frame.assert_pixel(55, 32, BLUE);
+
+    let frame = render(Msaa::Off, scene(OutlineOcclusion::Visible));
+    frame.assert_pixel(19, 32, RED);
+    frame.assert_pixel(37, 32, BLUE);
+    frame.assert_pixel(45, 32, BLUE);
+}
+
+/// Inner bands lie on the mesh, outer ones around it, and centered ones
+/// straddle its silhouette with half of the width on each side.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn placements_pick_the_side_of_the_silhouette() {
+    let render_placement = |placement| {
+        render(Msaa::Off, move |world: &mut World| {
+            spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(
+                MeshOutline::new(6.0)
+                    .with_color(RED.into())
+                    .with_placement(placement),
+            );
+        })
+    };
+
+    // The right edge of the cube is between pixels 41 and 42.
+    let frame = render_placement(OutlinePlacement::Outer);
+    frame.assert_pixel(38, 32, WHITE);
+    frame.assert_pixel(45, 32, RED);
+
+    let frame = render_placement(OutlinePlacement::Inner);
+    frame.assert_pixel(32, 32, WHITE);
+    frame.assert_pixel(38, 32, RED);
+    frame.assert_pixel(45, 32, BLACK);
+
+    let frame = render_placement(OutlinePlacement::Centered);
+    frame.assert_pixel(37, 32, WHITE);
+    frame.assert_pixel(40, 32, RED);
+    frame.assert_pixel(43, 32, RED);
+    frame.assert_pixel(47, 32, BLACK);
+}
+
+/// Fills tint the mesh's own pixels where it is in view.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn fills_tint_the_visible_mesh() {
+    let frame = render(Msaa::Off, |world: &mut World| {
+        // Hides the bottom half of the cube.
+        spawn_cuboid(
+            world,
+            Vec3::new(64.0, 32.0, 1.0),
+            Vec3::new(0.0, -16.0, 20.0),
+            BLACK,
+        );
+        spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(
+            MeshOutline::new(6.0)
+                .with_color(RED.into())
+                .with_fill(OutlineFill::new(BLUE.into()).with_opacity(0.5)),
+        );
+    });
+
+    // Half way from white to blue, in linear color.
+    frame.assert_pixel(32, 27, LinearRgba::rgb(0.5, 0.5, 1.0).into());
+    frame.assert_pixel(19, 27, RED);
+    frame.assert_pixel(32, 37, BLACK);
+}
+
+/// With two overlap candidates, the band of an outline that loses a pixel is
+/// still drawn below the winner's instead of being cut off.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn runner_up_outlines_show_below_the_winner() {
+    let render_candidates = |overlap_candidates| {
+        render(Msaa::Off, move |world: &mut World| {
+            set_camera(
+                world,
+                OutlineCameraSettings::default().with_overlap_candidates(overlap_candidates),
+            );
+
+            // Two cubes 6 pixels apart, both bands covering the gap between
+            // them. The left one wins it on priority, with a translucent band.
+            spawn_cuboid(world, Vec3::splat(10.0), Vec3::new(-8.0, 0.0, 0.0), WHITE).insert(
+                MeshOutline::new(6.0)
+                    .with_color(RED.with_alpha(0.5).into())
+                    .with_priority(1),
+            );
+            spawn_cuboid(world, Vec3::splat(10.0), Vec3::new(8.0, 0.0, 0.0), WHITE)
+                .insert(MeshOutline::new(12.0).with_color(GREEN.into()));
+        })
+    };
+
+    let frame = render_candidates(1);
+    frame.assert_pixel(31, 32, LinearRgba::rgb(0.5, 0.0, 0.0).into());
+
+    let frame = render_candidates(2);
+    frame.assert_pixel(31, 32, LinearRgba::rgb(0.5, 0.5, 0.0).into());
+}
+
+/// Outlines that tie on priority and depth go to the mesh whose entity comes
+/// first, even when they look alike.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn tied_outlines_go_to_the_first_entity() {
+    let render_tied = |left_first: bool| {
+        render(Msaa::Off, move |world: &mut World| {
+            // Red up to half the width from the silhouette and green beyond,
+            // so pixels show whose band they are in.
+            let outline = MeshOutline::new(16.0).with_gradient(OutlineGradient::new([
+                (0.5, RED.into()),
+                (0.5, GREEN.into()),
+            ]));
+            // The cubes cover pixels 14 to 23 and 36 to 45 of each row, and
+            // their bands meet between them.
+            let mut cubes = [Vec3::new(-13.0, 0.0, 0.0), Vec3::new(9.0, 0.0, 0.0)];
+            if !left_first {
+                cubes.reverse();
+            }
+            for translation in cubes {
+                spawn_cuboid(world, Vec3::splat(10.0), translation, WHITE).insert(outline.clone());
+            }
+        })
+    };
+
+    let frame = render_tied(true);
+    frame.assert_pixel(25, 32, RED);
+    frame.assert_pixel(34, 32, GREEN);
+
+    let frame = render_tied(false);
+    frame.assert_pixel(25, 32, GREEN);
+    frame.assert_pixel(34, 32, RED);
+}
+
+/// Square corners keep the band around the cube's corners boxy, where round
+/// and diamond corners cut it off.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn corners_shape_the_band_around_corners() {
+    let render_corners = |corners| {
+        render(Msaa::Off, move |world: &mut World| {
+            spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(
+                MeshOutline::new(12.0)
+                    .with_color(RED.into())
+                    .with_corners(corners),
+            );
+        })
+    };
+
+    // (51, 51) is 10 pixels right of and below the cube's corner pixel.
+    let frame = render_corners(OutlineCorners::Square);
+    frame.assert_pixel(51, 32, RED);
+    frame.assert_pixel(51, 51, RED);
+
+    for corners in [OutlineCorners::Round, OutlineCorners::Diamond] {
+        let frame = render_corners(corners);
+        frame.assert_pixel(51, 32, RED);
+        frame.assert_pixel(51, 51, BLACK);
+    }
+}
+
+/// Anti-aliased bands fade out over their last pixel instead of ending on a
+/// hard edge.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn band_edges_are_smoothed_when_antialiasing() {
+    let render_antialias = |antialias| {
+        render(Msaa::Off, move |world: &mut World| {
+            set_camera(
+                world,
+                OutlineCameraSettings::default().with_antialias(antialias),
+            );
+            spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE)
+                .insert(MeshOutline::new(6.0).with_color(RED.into()));
+        })
+    };
+
+    // Pixel 47 is exactly the width away from the cube's last column.
+    let frame = render_antialias(true);
+    frame.assert_pixel(46, 32, RED);
+    frame.assert_pixel(47, 32, LinearRgba::rgb(0.5, 0.0, 0.0).into());
+
+    let frame = render_antialias(false);
+    frame.assert_pixel(46, 32, RED);
+    frame.assert_pixel(47, 32, BLACK);
+}
+
+/// With MSAA, a pixel whose samples are split by an occluder's edge takes the
+/// occluded color for the hidden share of them.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn msaa_occlusion_covers_part_of_a_pixel() {
+    let frame = render(Msaa::Sample4, |world: &mut World| {
+        // An occluder whose left edge splits pixel column 45 in half.
+        spawn_cuboid(
+            world,
+            Vec3::new(32.0, 64.0, 1.0),
+            Vec3::new(29.5, 0.0, 20.0),
+            BLUE,
+        );
+        spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(
+            MeshOutline::new(8.0)
+                .with_color(RED.into())
+                .with_occlusion(OutlineOcclusion::XRay {
+                    color: GREEN.into(),
+                }),
+        );
+    });
+
+    frame.assert_pixel(44, 32, RED);
+    frame.assert_pixel(45, 32, LinearRgba::rgb(0.5, 0.5, 0.0).into());
+    frame.assert_pixel(46, 32, GREEN);
+}
+
+/// Contact lines are drawn where the mesh passes from in front of the scene to
+/// just behind it.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn contact_lines_follow_where_meshes_cut_through_geometry() {
+    let yellow = Srgba::rgb(1.0, 1.0, 0.0);
+    let render_contact = |contact_line: Option<OutlineContactLine>| {
+        render(Msaa::Off, move |world: &mut World| {
+            // A thin slab through the cube's front face, behind it left of
+            // the middle and in front of it to the right.
+            spawn_cuboid(world, Vec3::new(100.0, 64.0, 0.1), Vec3::ZERO, BLUE).insert(
+                Transform::from_xyz(0.0, 0.0, CUBE / 2.0)
+                    .with_rotation(Quat::from_rotation_y(-FRAC_PI_4)),
+            );
+            let mut outline = MeshOutline::new(4.0).with_color(RED.into());
+            outline.contact_line = contact_line;
+            spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(outline);
+        })
+    };
+
+    let frame = render_contact(Some(OutlineContactLine::new(yellow.into())));
+    frame.assert_pixel(28, 32, WHITE);
+    frame.assert_pixel(31, 32, yellow);
+    frame.assert_pixel(32, 32, yellow);
+    frame.assert_pixel(36, 32, BLUE);
+
+    let frame = render_contact(None);
+    frame.assert_pixel(31, 32, WHITE);
+    frame.assert_pixel(32, 32, BLUE);
+}
+
+/// Offset outlines draw the band around the silhouette moved on screen,
+/// covering the moved silhouette but not the mesh itself.
+#[test]
+#[ignore = "requires a GPU adapter; run with: cargo test -- --ignored"]
+fn offset_bands_are_drawn_around_the_moved_silhouette() {
+    let render_offset = |offset: Option<OutlineOffset>| {
+        render(Msaa::Off, move |world: &mut World| {
+            let mut outline = MeshOutline::new(4.0).with_color(RED.into());
+            outline.offset = offset;
+            spawn_cuboid(world, Vec3::splat(CUBE), Vec3::ZERO, WHITE).insert(outline);
+        })
+    };
+
+    // Down and to the right, so the moved silhouette covers pixels 28 to 47.
+    let frame = render_offset(Some(OutlineOffset::new(Vec2::splat(6.0))));
+    frame.assert_pixel(32, 32, WHITE);
+    frame.assert_pixel(45, 45, RED);
+    frame.assert_pixel(50, 32, RED);
+    frame.assert_pixel(19, 32, BLACK);
+
+    let frame = render_offset(None);
+    frame.assert_pixel(45, 45, BLACK);
+    frame.assert_pixel(50, 32, BLACK);
+    frame.assert_pixel(19, 32, RED);
+}
+
+/// A rendered image, as sRGB bytes.
+struct Frame(Vec<u8>);
+
+impl Frame {
+    fn pixel(&self, x: u32, y: u32) -> Srgba {
+        let i = ((y * SIZE + x) * 4) as usize;
+        let [r, g, b, a] = self.0[i..i + 4] else {
+            unreachable!()
+        };
+        Srgba::rgba_u8(r, g, b, a)
+    }
+
+    #[track_caller]
+    fn assert_pixel(&self, x: u32, y: u32, expected: Srgba) {
+        let pixel = self.pixel(x, y);
+        let difference = (pixel.to_vec4() - expected.to_vec4()).abs().max_element();
+        assert!(
+            difference < 0.05,
+            "pixel ({x}, {y}) is {pixel:?}, expected {expected:?}"
+        );
+    }
+}
+
+/// Renders what `setup` spawns and reads the image back.
+fn render(msaa: Msaa, setup: impl FnOnce(&mut World)) -> Frame {
+    let mut app = App::new();
+
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            // Winit would create an event loop on this (non-main) test thread,
+            // which panics on macOS. We drive frames manually with `update()`.
+            .disable::<WinitPlugin>()
+            // Renders each frame in the `update()` that extracts it.
+            .disable::<PipelinedRenderingPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            })
+            .set(RenderPlugin {
+                // Ensure pipelines are ready when the frames below run.
+                synchronous_pipeline_compilation: true,
+                ..default()
+            }),
+    )
+    .add_plugins(MeshOutlinePlugin);
+
+    app.finish();
+    app.cleanup();
+
+    let world = app.world_mut();
+    let target = world
+        .resource_mut::<Assets<Image>>()
+        .add(Image::new_target_texture(
+            SIZE,
+            SIZE,
+            TextureFormat::Rgba8UnormSrgb,
+            None,
+        ));
+    world.spawn((
+        Camera3d::default(),
+        Camera {
+            clear_color: ClearColorConfig::Custom(Color::BLACK),
+            ..default()
+        },
+        RenderTarget::Image(target.clone().into()),
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical {
+                viewport_height: SIZE as f32,
+            },
+            ..OrthographicProjection::default_3d()
+        }),
+        Transform::from_xyz(0.0, 0.0, 50.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Tonemapping::None,
+        OutlineCamera,
+        DepthPrepass,
+        msaa,
+    ));
+    setup(world);
+
+    // Let pipelines compile and the outline passes run.
+    for _ in 0..8 {
+        app.update();
+    }
+
+    let image = Arc::new(Mutex::new(None));
+    let readback = image.clone();
+    app.world_mut().spawn(Readback::texture(target)).observe(
+        move |complete: On<ReadbackComplete>| {
+            *readback.lock().unwrap() = Some(complete.data.clone());
+        },
+    );
+    for _ in 0..8 {
+        app.update();
+        if let Some(data) = image.lock().unwrap().take() {
+            return Frame(data);
+        }
+    }
+    panic!("the image was never read back");
+}
+
+/// Gives the `OutlineCamera` that `render` spawned the given settings.
+fn set_camera(world: &mut World, settings: OutlineCameraSettings) {
+    let camera = world
+        .query_filtered::<Entity, With<OutlineCamera>>()
+        .single(world)
+        .unwrap();
+    world.entity_mut(camera).insert(settings);
+}
+
+/// Spawns an unlit cuboid of `size` centered on `translation`.
+fn spawn_cuboid(
+    world: &mut World,
+    size: Vec3,
+    translation: Vec3,
+    color: Srgba,
+) -> EntityWorldMut<'_> {
+    let mesh = world
+        .resource_mut::<Assets<Mesh>>()
+        .add(Cuboid::from_size(size));
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial {
+            base_color: color.into(),
+            unlit: true,
+            ..default()
+        });
+    world.spawn((
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::from_translation(translation),
+    ))
+}